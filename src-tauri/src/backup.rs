@@ -0,0 +1,317 @@
+//! Portable JSON backups.
+//!
+//! A backup holds everything a user would miss on a new machine: lists and
+//! their todos (trash and archive included), the event log behind the
+//! statistics, saved views, reset schedules, notes with their sticky
+//! windows, the main window and the raw settings table. It does not follow
+//! the database schema, so it stays readable after migrations. Undo history
+//! and note revisions are left out.
+//!
+//! `VERSION` goes up when a field changes meaning. New optional fields get
+//! `#[serde(default)]` instead, so older backups keep loading.
+
+use crate::db::{EventKind, Priority, WindowState};
+use crate::error::{AppError, Result};
+use crate::query::{Query, ViewLayout};
+use crate::recurrence::RecurrenceRule;
+use crate::schedule::{self, ResetFrequency};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Written to `format` so a stray JSON file is not mistaken for a backup.
+pub const FORMAT: &str = "sticky-notes-backup";
+
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backup {
+    pub format: String,
+    pub version: u32,
+    /// Unix time in milliseconds.
+    pub exported_at: i64,
+    pub lists: Vec<BackupList>,
+    pub todos: Vec<BackupTodo>,
+    /// Events that are not undone, oldest first.
+    #[serde(default)]
+    pub events: Vec<BackupEvent>,
+    #[serde(default)]
+    pub views: Vec<BackupView>,
+    #[serde(default)]
+    pub reset_schedules: Vec<BackupSchedule>,
+    pub notes: Vec<BackupNote>,
+    /// Size, position and pin of the main window.
+    #[serde(default)]
+    pub window: Option<WindowState>,
+    /// Every row of the settings table, as stored.
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupList {
+    pub id: u32,
+    pub name: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupTodo {
+    pub id: u32,
+    pub list_id: u32,
+    pub parent_id: Option<u32>,
+    pub position: i32,
+    pub text: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub completed: bool,
+    /// Unix time in milliseconds.
+    #[serde(default)]
+    pub completed_at: Option<i64>,
+    #[serde(default)]
+    pub target_count: Option<i32>,
+    /// What is left of `target_count`.
+    #[serde(default)]
+    pub current_count: i32,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    pub due_at: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    pub scheduled_for: Option<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub recurrence: Option<RecurrenceRule>,
+    /// Unix time in milliseconds, set for items in the trash.
+    #[serde(default)]
+    pub deleted_at: Option<i64>,
+    /// Unix time in milliseconds, set for archived items.
+    #[serde(default)]
+    pub archived_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupEvent {
+    /// May name an item that has since been purged.
+    pub todo_id: Option<u32>,
+    pub list_id: u32,
+    pub kind: EventKind,
+    #[serde(default)]
+    pub value: Option<i64>,
+    /// Unix time in milliseconds.
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupView {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub layout: ViewLayout,
+    pub position: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub list_id: u32,
+    /// The item to reset, the whole list when absent.
+    #[serde(default)]
+    pub todo_id: Option<u32>,
+    pub frequency: ResetFrequency,
+    /// Local time, `HH:MM`.
+    pub time: String,
+    /// Unix time in milliseconds.
+    pub last_reset_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupNote {
+    pub id: u32,
+    pub title: String,
+    pub content: String,
+    pub position: i32,
+    /// Unix time in milliseconds.
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+    /// The note's own sticky window, if it has one.
+    #[serde(default)]
+    pub window: Option<WindowState>,
+}
+
+/// How a restore treats what is already there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Drop everything and load the backup with its own ids.
+    Replace,
+    /// Add the backup's lists, notes and views next to the existing ones
+    /// under new ids. Settings and windows stay as they are, and events
+    /// about items or lists missing from the backup are skipped.
+    Merge,
+}
+
+/// What a restore added, for the confirmation message.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub lists: u32,
+    pub todos: u32,
+    pub notes: u32,
+    pub views: u32,
+}
+
+impl Backup {
+    /// Parses and checks a backup file. References between records are
+    /// checked here so a bad file fails before anything is written.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let backup: Backup = serde_json::from_str(json)
+            .map_err(|e| AppError::InvalidInput(format!("Not a valid backup: {}", e)))?;
+        if backup.format != FORMAT {
+            return Err(AppError::InvalidInput("Not a valid backup: unknown format".to_string()));
+        }
+        if backup.version > VERSION {
+            return Err(AppError::InvalidInput(format!(
+                "Backup version {} is newer than this app supports ({})",
+                backup.version, VERSION
+            )));
+        }
+        backup.validate()?;
+        Ok(backup)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::Io(format!("Could not write backup: {}", e)))
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(AppError::InvalidInput(format!("Not a valid backup: {}", msg)));
+
+        if self.lists.is_empty() {
+            return invalid("it has no lists".to_string());
+        }
+        if self.notes.is_empty() {
+            return invalid("it has no notes".to_string());
+        }
+        let lists: HashSet<u32> = self.lists.iter().map(|l| l.id).collect();
+        let notes: HashSet<u32> = self.notes.iter().map(|n| n.id).collect();
+        if lists.len() != self.lists.len() || notes.len() != self.notes.len() {
+            return invalid("duplicate ids".to_string());
+        }
+
+        let todos: HashMap<u32, &BackupTodo> = self.todos.iter().map(|t| (t.id, t)).collect();
+        if todos.len() != self.todos.len() {
+            return invalid("duplicate todo ids".to_string());
+        }
+        for todo in &self.todos {
+            if !lists.contains(&todo.list_id) {
+                return invalid(format!("todo {} is in unknown list {}", todo.id, todo.list_id));
+            }
+            if let Some(parent) = todo.parent_id {
+                match todos.get(&parent) {
+                    Some(p) if p.list_id == todo.list_id => {}
+                    _ => return invalid(format!("todo {} has unknown parent {}", todo.id, parent)),
+                }
+            }
+        }
+        // Checked like the editors check them, or they would fail on every
+        // load and never fire
+        for view in &self.views {
+            if let Err(e) = Query::parse(&view.query) {
+                return invalid(format!("view \"{}\": {}", view.name, e));
+            }
+        }
+        for schedule in &self.reset_schedules {
+            schedule.frequency.validate()?;
+            if let Err(e) = schedule::parse_time(&schedule.time) {
+                return invalid(format!("reset schedule for list {}: {}", schedule.list_id, e));
+            }
+            let todo_list = schedule.todo_id.map(|id| todos.get(&id).map(|t| t.list_id));
+            if !lists.contains(&schedule.list_id) || todo_list.is_some_and(|l| l != Some(schedule.list_id)) {
+                return invalid(format!("reset schedule for unknown list {}", schedule.list_id));
+            }
+        }
+        self.todos_parents_first().map(|_| ())
+    }
+
+    /// The todos ordered so every parent comes before its children, which
+    /// is the order they can be inserted in. Fails on a parent cycle.
+    pub fn todos_parents_first(&self) -> Result<Vec<&BackupTodo>> {
+        let mut children: HashMap<Option<u32>, Vec<&BackupTodo>> = HashMap::new();
+        for todo in &self.todos {
+            children.entry(todo.parent_id).or_default().push(todo);
+        }
+
+        let mut ordered = Vec::with_capacity(self.todos.len());
+        let mut stack: Vec<&BackupTodo> = children.remove(&None).unwrap_or_default();
+        while let Some(todo) = stack.pop() {
+            ordered.push(todo);
+            stack.extend(children.remove(&Some(todo.id)).unwrap_or_default());
+        }
+        // Whatever was never reached hangs off a cycle
+        if ordered.len() != self.todos.len() {
+            return Err(AppError::InvalidInput("Not a valid backup: todos form a parent cycle".to_string()));
+        }
+        Ok(ordered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(todos: &str) -> String {
+        format!(
+            r#"{{"format": "{}", "version": 1, "exported_at": 0,
+                "lists": [{{"id": 1, "name": "Todos", "position": 0}}],
+                "todos": {},
+                "notes": [{{"id": 1, "title": "Note", "content": "", "position": 0}}]}}"#,
+            FORMAT, todos
+        )
+    }
+
+    fn todo(id: u32, parent_id: Option<u32>) -> String {
+        format!(
+            r#"{{"id": {}, "list_id": 1, "parent_id": {}, "position": 0, "text": "Item", "completed": false}}"#,
+            id,
+            parent_id.map_or("null".to_string(), |p| p.to_string())
+        )
+    }
+
+    #[test]
+    fn orders_parents_first() {
+        let json = backup(&format!("[{}, {}, {}]", todo(3, Some(2)), todo(2, Some(1)), todo(1, None)));
+        let backup = Backup::from_json(&json).unwrap();
+        let order: Vec<u32> = backup.todos_parents_first().unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(order, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_broken_backups() {
+        let invalid = |json: &str| matches!(Backup::from_json(json), Err(AppError::InvalidInput(_)));
+        assert!(invalid("{}"));
+        assert!(invalid(&backup("[]").replace(FORMAT, "something-else")));
+        assert!(invalid(&backup("[]").replace(r#""version": 1"#, r#""version": 99"#)));
+        assert!(invalid(&backup(&format!("[{}, {}]", todo(1, None), todo(1, None)))));
+        assert!(invalid(&backup(&format!("[{}]", todo(1, Some(7))))));
+        assert!(invalid(&backup(&format!("[{}, {}]", todo(1, Some(2)), todo(2, Some(1))))));
+    }
+
+    #[test]
+    fn rejects_bad_views_and_schedule_times() {
+        let with = |field: &str, value: &str| {
+            backup("[]").replacen('{', &format!("{{\"{}\": {}, ", field, value), 1)
+        };
+        let view = |query: &str| format!(r#"[{{"name": "Soon", "query": "{}", "position": 0}}]"#, query);
+        assert!(Backup::from_json(&with("views", &view("due<7d"))).is_ok());
+        assert!(matches!(Backup::from_json(&with("views", &view("due<soon"))), Err(AppError::InvalidInput(_))));
+
+        let schedule = |time: &str| {
+            format!(r#"[{{"list_id": 1, "frequency": {{"kind": "daily"}}, "time": "{}", "last_reset_at": 0}}]"#, time)
+        };
+        assert!(Backup::from_json(&with("reset_schedules", &schedule("07:30"))).is_ok());
+        assert!(matches!(
+            Backup::from_json(&with("reset_schedules", &schedule("25:99"))),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+//! Todo trees as GitHub-flavored Markdown task lists.
+//!
+//! Each item becomes `- [ ] text` or `- [x] text`, children indented by two
+//! spaces under their parent, siblings in `position` order. A priority is
+//! written as its `!marker` and a countdown keeps its progress as
+//! `(done/target)` after the text. `parse` reads the same shape back, and
+//! anything close to it: `*` and numbered lists, items without a checkbox,
+//! and plain lines outside a list.
+
+use crate::db::TodoItem;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::collections::HashMap;
+use std::ops::Range;
+
+const INDENT: &str = "  ";
+
+/// Renders the live items of one list, given in sibling order.
+pub fn to_markdown(todos: &[TodoItem]) -> String {
+    let mut children: HashMap<Option<u32>, Vec<&TodoItem>> = HashMap::new();
+    for todo in todos {
+        children.entry(todo.parent_id).or_default().push(todo);
+    }
+
+    let mut out = String::new();
+    let mut stack: Vec<(&TodoItem, usize)> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|t| (*t, 0)).collect())
+        .unwrap_or_default();
+    while let Some((todo, depth)) = stack.pop() {
+        out.push_str(&INDENT.repeat(depth));
+        out.push_str(&item_line(todo));
+        out.push('\n');
+        if let Some(kids) = children.get(&Some(todo.id)) {
+            stack.extend(kids.iter().rev().map(|t| (*t, depth + 1)));
+        }
+    }
+    out
+}
+
+fn item_line(todo: &TodoItem) -> String {
+    let check = if todo.completed { 'x' } else { ' ' };
+    let mut line = format!("- [{}] {}", check, todo.text.trim());
+    if let Some(marker) = todo.priority.marker() {
+        line.push_str(&format!(" !{}", marker));
+    }
+    if let Some(target) = todo.target_count.filter(|t| *t > 0) {
+        let done = if todo.completed { target } else { (target - todo.current_count).clamp(0, target) };
+        line.push_str(&format!(" ({}/{})", done, target));
+    }
+    line
+}
+
+/// One item read from a checklist, with its sub-items in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineItem {
+    pub text: String,
+    pub completed: bool,
+    /// `(target, remaining)` from a trailing `(done/target)`, stored the way
+    /// a countdown keeps it.
+    pub count: Option<(i32, i32)>,
+    pub children: Vec<OutlineItem>,
+}
+
+#[derive(Default)]
+struct OpenItem {
+    text: Option<Range<usize>>,
+    completed: bool,
+    children: Vec<OutlineItem>,
+}
+
+/// Reads a Markdown outline into items. Completion follows the same rules
+/// as ticking items by hand: a checked item completes everything under it,
+/// and an item whose sub-items are all done is done. Items with no text
+/// are dropped and their sub-items move up a level.
+pub fn parse(markdown: &str) -> Vec<OutlineItem> {
+    let mut roots = Vec::new();
+    let mut open: Vec<OpenItem> = Vec::new();
+    let mut block: Option<Range<usize>> = None;
+
+    for (event, range) in Parser::new_ext(markdown, Options::ENABLE_TASKLISTS).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Item) => open.push(OpenItem::default()),
+            Event::End(TagEnd::Item) => {
+                let Some(item) = open.pop() else { continue };
+                let text = item.text.map(|r| flatten(&markdown[r])).unwrap_or_default();
+                let finished = if text.is_empty() {
+                    item.children
+                } else {
+                    let (text, count) = split_count(&text);
+                    vec![OutlineItem { text, completed: item.completed, count, children: item.children }]
+                };
+                match open.last_mut() {
+                    Some(parent) => parent.children.extend(finished),
+                    None => roots.extend(finished),
+                }
+            }
+            Event::TaskListMarker(checked) => {
+                if let Some(item) = open.last_mut() {
+                    item.completed = checked;
+                }
+            }
+            Event::Start(Tag::Paragraph | Tag::Heading { .. }) if open.is_empty() => block = Some(range),
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_)) if open.is_empty() => {
+                let Some(r) = block.take() else { continue };
+                for line in markdown[r].lines() {
+                    let text = flatten(line.trim_start_matches('#'));
+                    if !text.is_empty() {
+                        let (text, count) = split_count(&text);
+                        roots.push(OutlineItem { text, completed: false, count, children: Vec::new() });
+                    }
+                }
+            }
+            Event::Text(_)
+            | Event::Code(_)
+            | Event::InlineHtml(_)
+            | Event::InlineMath(_)
+            | Event::SoftBreak
+            | Event::HardBreak
+            | Event::Start(
+                Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. },
+            ) => {
+                // Text after a nested list belongs to the nested items' parent
+                // only in loose lists; it is rare enough to leave out.
+                if let Some(item) = open.last_mut().filter(|i| i.children.is_empty()) {
+                    item.text = Some(match item.text.take() {
+                        Some(r) => r.start.min(range.start)..r.end.max(range.end),
+                        None => range,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for item in &mut roots {
+        settle(item, false);
+    }
+    roots
+}
+
+/// Joins wrapped lines and collapses runs of whitespace.
+fn flatten(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Splits a trailing `(done/target)` off `text`.
+fn split_count(text: &str) -> (String, Option<(i32, i32)>) {
+    let parsed = text.strip_suffix(')').and_then(|rest| rest.rsplit_once('(')).and_then(|(before, inner)| {
+        let (done, target) = inner.split_once('/')?;
+        let (done, target): (i32, i32) = (done.trim().parse().ok()?, target.trim().parse().ok()?);
+        (target > 0 && (0..=target).contains(&done)).then(|| (before.trim_end(), (target, target - done)))
+    });
+    match parsed {
+        Some((before, count)) if !before.is_empty() => (before.to_string(), Some(count)),
+        _ => (text.to_string(), None),
+    }
+}
+
+fn settle(item: &mut OutlineItem, parent_completed: bool) {
+    item.completed |= parent_completed || matches!(item.count, Some((_, 0)));
+    for child in &mut item.children {
+        settle(child, item.completed);
+    }
+    if !item.children.is_empty() {
+        item.completed = item.children.iter().all(|c| c.completed);
+    }
+    if let Some((target, remaining)) = item.count.as_mut() {
+        if item.completed {
+            *remaining = 0;
+        } else if *remaining == 0 {
+            *remaining = *target;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, completed: bool, children: Vec<OutlineItem>) -> OutlineItem {
+        OutlineItem { text: text.to_string(), completed, count: None, children }
+    }
+
+    #[test]
+    fn parses_nested_task_lists() {
+        let items = parse("- [ ] Trip\n  - [x] Tickets\n  - [ ] Hotel\n- [x] Laundry\n");
+        assert_eq!(
+            items,
+            vec![
+                item("Trip", false, vec![item("Tickets", true, vec![]), item("Hotel", false, vec![])]),
+                item("Laundry", true, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn settles_completion_like_ticking_by_hand() {
+        // A checked parent completes its children, finished children their parent
+        let items = parse("- [x] Done\n  - [ ] Child\n- [ ] Open\n  - [x] Child\n");
+        assert!(items[0].children[0].completed);
+        assert!(items[1].completed);
+    }
+
+    #[test]
+    fn reads_counts_and_loose_markdown() {
+        let items = parse("# Groceries\n\n* Milk (1/3)\n1. Eggs (2/2)\n- Bread (4/3)\n");
+        assert_eq!(items[0], item("Groceries", false, vec![]));
+        assert_eq!(items[1].count, Some((3, 2)));
+        assert!(items[2].completed);
+        assert_eq!(items[2].count, Some((2, 0)));
+        assert_eq!((items[3].text.as_str(), items[3].count), ("Bread (4/3)", None));
+    }
+}
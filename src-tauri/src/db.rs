@@ -1,7 +1,24 @@
-use rusqlite::{params, Connection, Result};
+use crate::backup::{
+    self, Backup, BackupEvent, BackupList, BackupNote, BackupSchedule, BackupTodo, BackupView, RestoreMode,
+    RestoreSummary,
+};
+use crate::checklist;
+use crate::error::{AppError, Result};
+use crate::query::{Query, ViewLayout};
+use crate::recurrence::{self, RecurrenceMode, RecurrenceRule};
+use crate::revisions::{self, DiffLine, NoteRevision};
+use crate::schedule::{self, ResetFrequency};
+use crate::search::{self, HitKind, SearchHit};
+use crate::stats::{self, ListRate, Period, Stats, Streak};
+use crate::{history, markers, migrations};
+use chrono::NaiveDate;
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
-use tauri::Manager;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WindowState {
@@ -12,176 +29,2596 @@ pub struct WindowState {
     pub pinned: bool,
 }
 
+/// A note as listed in the note picker, without its content.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteSummary {
+    pub id: u32,
+    pub title: String,
+    pub position: i32,
+    /// Unix time in milliseconds of the last content change.
+    pub updated_at: Option<i64>,
+    /// Whether the note has a sticky window of its own.
+    pub windowed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Note {
+    pub id: u32,
+    pub title: String,
+    pub content: String,
+    pub updated_at: Option<i64>,
+}
+
+/// A named, independently ordered tree of todos.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct List {
+    pub id: u32,
+    pub name: String,
+    pub position: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TodoItem {
     pub id: u32,
+    pub list_id: u32,
     pub text: String,
     pub completed: bool,
     pub parent_id: Option<u32>,
     pub position: i32,
     pub target_count: Option<i32>,
     pub current_count: i32,
+    /// `YYYY-MM-DD`
+    pub due_at: Option<String>,
+    /// `YYYY-MM-DD`
+    pub scheduled_for: Option<String>,
+    pub priority: Priority,
+    /// Names from the `todo_tags` table, filled in by `get_todos`.
+    pub tags: Vec<String>,
+    pub recurrence: Option<RecurrenceRule>,
+    /// Markdown shown in the item's detail pane.
+    pub description: Option<String>,
+}
+
+/// Column list matching `TodoItem::from_row`.
+const TODO_COLUMNS: &str = "id, list_id, text, completed, parent_id, position, target_count, current_count, \
+     due_at, scheduled_for, priority, recurrence, description";
+
+impl TodoItem {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(TodoItem {
+            id: row.get(0)?,
+            list_id: row.get(1)?,
+            text: row.get(2)?,
+            completed: row.get(3)?,
+            parent_id: row.get(4)?,
+            position: row.get(5)?,
+            target_count: row.get(6)?,
+            current_count: row.get(7)?,
+            due_at: row.get(8)?,
+            scheduled_for: row.get(9)?,
+            priority: row.get(10)?,
+            tags: Vec::new(),
+            recurrence: row
+                .get::<_, Option<String>>(11)?
+                .as_deref()
+                .and_then(RecurrenceRule::from_json),
+            description: row.get(12)?,
+        })
+    }
+}
+
+/// Stored as an integer so `ORDER BY priority DESC` puts urgent items first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    #[default]
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+    Urgent = 4,
+}
+
+impl Priority {
+    /// Parses the word after a `!` marker, e.g. `high` in `!high`.
+    pub fn from_marker(word: &str) -> Option<Self> {
+        match word.to_ascii_lowercase().as_str() {
+            "low" => Some(Priority::Low),
+            "med" | "medium" => Some(Priority::Medium),
+            "high" => Some(Priority::High),
+            "urgent" => Some(Priority::Urgent),
+            _ => None,
+        }
+    }
+
+    /// The word `from_marker` reads back, `None` for no priority.
+    pub fn marker(self) -> Option<&'static str> {
+        match self {
+            Priority::None => None,
+            Priority::Low => Some("low"),
+            Priority::Medium => Some("medium"),
+            Priority::High => Some("high"),
+            Priority::Urgent => Some("urgent"),
+        }
+    }
+}
+
+impl ToSql for Priority {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(*self as i64))
+    }
+}
+
+impl FromSql for Priority {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Ok(match value.as_i64()? {
+            i64::MIN..=0 => Priority::None,
+            1 => Priority::Low,
+            2 => Priority::Medium,
+            3 => Priority::High,
+            _ => Priority::Urgent,
+        })
+    }
+}
+
+/// How siblings are ordered by `get_todos`. Either way `position` keeps the
+/// manual order, so switching back restores it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    #[default]
+    Manual,
+    /// Highest priority first, manual order within the same priority.
+    Priority,
+}
+
+/// How note mode shows the note.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteView {
+    #[default]
+    Edit,
+    Preview,
+    /// Editor and preview side by side.
+    Split,
+}
+
+/// The main window's top-level tab, restored at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tab {
+    #[default]
+    Todos,
+    Notes,
+}
+
+/// A tag with the number of live items carrying it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub count: u32,
+}
+
+/// Resets a list, or one subtree of it, at a fixed local time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetSchedule {
+    pub id: u32,
+    pub list_id: u32,
+    /// Root of the subtree to reset, `None` for the whole list.
+    pub todo_id: Option<u32>,
+    pub todo_text: Option<String>,
+    pub frequency: ResetFrequency,
+    /// Local time, `HH:MM`.
+    pub time: String,
+    /// Unix time in milliseconds.
+    pub last_reset_at: i64,
+}
+
+/// A named query, see `query.rs`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedView {
+    pub id: u32,
+    pub name: String,
+    pub query: String,
+    pub layout: ViewLayout,
+    pub position: i32,
+}
+
+/// The items a query matched plus, for the tree layout, their ancestors.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub todos: Vec<TodoItem>,
+    /// Ids of the items that matched themselves.
+    pub matches: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Completed,
+    Uncompleted,
+    /// `value` is the count left.
+    Decremented,
+    /// `todo_id` is `None` when the whole list was reset.
+    Reset,
+    /// `value` is the number of items that went to the trash.
+    Deleted,
+}
+
+impl EventKind {
+    const ALL: [EventKind; 5] = [
+        EventKind::Completed,
+        EventKind::Uncompleted,
+        EventKind::Decremented,
+        EventKind::Reset,
+        EventKind::Deleted,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            EventKind::Completed => "completed",
+            EventKind::Uncompleted => "uncompleted",
+            EventKind::Decremented => "decremented",
+            EventKind::Reset => "reset",
+            EventKind::Deleted => "deleted",
+        }
+    }
+}
+
+impl FromSql for EventKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let kind = value.as_str()?;
+        EventKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == kind)
+            .ok_or(rusqlite::types::FromSqlError::InvalidType)
+    }
+}
+
+/// An entry of the `todo_events` log.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoEvent {
+    pub id: i64,
+    pub todo_id: Option<u32>,
+    pub list_id: u32,
+    /// Current text of the item, `None` once it has been purged.
+    pub text: Option<String>,
+    pub kind: EventKind,
+    pub value: Option<i64>,
+    /// Unix time in milliseconds.
+    pub created_at: i64,
+}
+
+/// A completed item archived together with its subtree.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub id: u32,
+    pub text: String,
+    pub list_name: String,
+    /// Unix time in milliseconds.
+    pub archived_at: i64,
+    /// The entry's descendants, depth first in position order.
+    pub items: Vec<ArchivedItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedItem {
+    pub id: u32,
+    pub text: String,
+    /// 1 for children of the entry, 2 for grandchildren, ...
+    pub depth: u32,
+    pub completed: bool,
+}
+
+/// How `delete_todo` treats the children of the deleted item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMode {
+    /// Move the item together with its whole subtree to the trash.
+    Cascade,
+    /// Trash only the item; its children take its place under its parent.
+    Reparent,
+}
+
+/// A subtree in the trash, listed by its top-most item.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: u32,
+    pub text: String,
+    pub parent_id: Option<u32>,
+    pub list_name: String,
+    /// Unix time in milliseconds.
+    pub deleted_at: i64,
+    /// Number of items that will come back on restore, including this one.
+    pub item_count: u32,
+}
+
+pub(crate) const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Trash entries older than this are purged unless the user changes it.
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Owns the app's single SQLite connection. Held in Tauri managed state,
+/// but usable on its own, e.g. `TodoStore::open(":memory:")`.
+pub struct TodoStore {
+    conn: Mutex<Connection>,
+}
+
+impl TodoStore {
+    /// Opens the database at `path`, creating it if needed, and migrates it
+    /// to the latest schema.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut conn = Connection::open(path)?;
+        Self::configure(&conn)?;
+
+        let backup_path = if path == Path::new(":memory:") { None } else { Some(path) };
+        migrations::migrate(&mut conn, backup_path)?;
+
+        // Databases written before foreign keys were enforced may contain
+        // children whose parent was deleted. They are invisible in the UI.
+        let repaired = repair_orphans(&mut conn)?;
+        if repaired > 0 {
+            println!("[DB] Repaired {} orphaned todos", repaired);
+        }
+        history::install(&conn)?;
+
+        let store = TodoStore { conn: Mutex::new(conn) };
+        store.purge_expired_trash()?;
+        Ok(store)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::open(":memory:")
+    }
+
+    fn configure(conn: &Connection) -> Result<()> {
+        // In-memory databases silently keep their own journal mode
+        let _: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        // SQLite defaults foreign keys to off for every new connection
+        conn.pragma_update(None, "foreign_keys", true)?;
+        Ok(())
+    }
+
+    // A panic while holding the lock leaves the connection itself intact.
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs a todo mutation in a transaction and records it in the undo history.
+    fn journaled<T>(&self, label: &str, f: impl FnOnce(&Transaction) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        history::begin(&tx)?;
+        let value = f(&tx)?;
+        match history::record(&tx, label)? {
+            // Events belong to the entry so undo can hide them again
+            Some(entry) => tx.execute(
+                "UPDATE todo_events SET history_id = ?1 WHERE history_id IS NULL",
+                params![entry],
+            )?,
+            // Nothing changed, so nothing happened either
+            None => tx.execute("DELETE FROM todo_events WHERE history_id IS NULL", [])?,
+        };
+        tx.commit()?;
+        Ok(value)
+    }
+
+    /// Runs a mutation the user did not make, like a scheduled reset, in a
+    /// transaction that stays out of the undo history and leaves redo alone.
+    fn unjournaled<T>(&self, f: impl FnOnce(&Transaction) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let value = f(&tx)?;
+        // Its events belong to no entry, see `restore_backup`
+        tx.execute("UPDATE todo_events SET history_id = 0 WHERE history_id IS NULL", [])?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    /// Reverts the last recorded mutation, returning its label.
+    pub fn undo(&self) -> Result<Option<String>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let step = history::undo(&tx)?;
+        if let history::Step::Applied(id, _) = &step {
+            tx.execute("UPDATE todo_events SET undone = 1 WHERE history_id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        step_result("undo", step)
+    }
+
+    /// Re-applies the last undone mutation, returning its label.
+    pub fn redo(&self) -> Result<Option<String>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let step = history::redo(&tx)?;
+        if let history::Step::Applied(id, _) = &step {
+            tx.execute("UPDATE todo_events SET undone = 0 WHERE history_id = ?1", params![id])?;
+        }
+        tx.commit()?;
+        step_result("redo", step)
+    }
+
+    /// Events of one item, or of all items, optionally limited to
+    /// `since <= created_at < until` (Unix milliseconds), oldest first.
+    pub fn get_todo_events(&self, todo_id: Option<u32>, since: Option<i64>, until: Option<i64>) -> Result<Vec<TodoEvent>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare(
+            "SELECT e.id, e.todo_id, e.list_id, t.text, e.kind, e.value, e.created_at
+             FROM todo_events e LEFT JOIN todos t ON t.id = e.todo_id
+             WHERE e.undone = 0
+               AND (?1 IS NULL OR e.todo_id = ?1)
+               AND (?2 IS NULL OR e.created_at >= ?2)
+               AND (?3 IS NULL OR e.created_at < ?3)
+             ORDER BY e.created_at, e.id",
+        )?;
+        let events = stmt
+            .query_map(params![todo_id, since, until], |row| {
+                Ok(TodoEvent {
+                    id: row.get(0)?,
+                    todo_id: row.get(1)?,
+                    list_id: row.get(2)?,
+                    text: row.get(3)?,
+                    kind: row.get(4)?,
+                    value: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(events)
+    }
+
+    /// Completion statistics for the weeks covering the last `days` days,
+    /// plus streaks over the whole history.
+    pub fn get_stats(&self, days: u32) -> Result<Stats> {
+        if days > stats::MAX_DAYS {
+            return Err(AppError::InvalidInput(format!(
+                "Statistics cover at most {} days",
+                stats::MAX_DAYS
+            )));
+        }
+        let conn = self.conn();
+        let today = chrono::Local::now().date_naive();
+        let from = stats::week_start(today - chrono::Duration::days(i64::from(days.max(1)) - 1));
+
+        let dates = conn
+            .prepare(
+                "SELECT created_at FROM todo_events
+                 WHERE kind = 'completed' AND undone = 0 AND created_at >= ?1",
+            )?
+            .query_map(params![stats::start_of_day(from)], |row| row.get::<_, i64>(0))?
+            .filter_map(|ms| ms.map(stats::local_date).transpose())
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let day_counts = stats::daily_counts(&dates, from, today);
+        let week_counts = stats::weekly_counts(&day_counts);
+
+        // Spawned occurrences are new rows with the same text under the same
+        // parent, so a streak follows that series rather than a single id
+        let candidates = conn
+            .prepare(
+                "SELECT id, list_id, parent_id, text, recurrence FROM todos
+                 WHERE deleted_at IS NULL AND archived_at IS NULL AND (recurrence IS NOT NULL OR target_count IS NOT NULL)
+                 ORDER BY id DESC",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, Option<u32>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut series_stmt = conn.prepare(
+            "SELECT e.created_at FROM todo_events e JOIN todos t ON t.id = e.todo_id
+             WHERE e.kind = 'completed' AND e.undone = 0
+               AND t.list_id = ?1 AND t.parent_id IS ?2 AND t.text = ?3",
+        )?;
+        let mut seen = std::collections::HashSet::new();
+        let mut streaks = Vec::new();
+        for (todo_id, list_id, parent_id, text, rule) in candidates {
+            if !seen.insert((list_id, parent_id, text.clone())) {
+                continue;
+            }
+            let completions = series_stmt
+                .query_map(params![list_id, parent_id, text], |row| row.get::<_, i64>(0))?
+                .filter_map(|ms| ms.map(stats::local_date).transpose())
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let period = Period::for_rule(rule.as_deref().and_then(RecurrenceRule::from_json).as_ref());
+            let (current, longest) = stats::streak(&completions, period, today);
+            if longest > 0 {
+                streaks.push(Streak { todo_id, list_id, text, period, current, longest });
+            }
+        }
+        streaks.sort_by_key(|s| std::cmp::Reverse((s.current, s.longest)));
+
+        let lists = conn
+            .prepare(
+                "SELECT l.id, l.name, COALESCE(SUM(t.completed), 0), COUNT(t.id)
+                 FROM lists l LEFT JOIN todos t ON t.list_id = l.id AND t.deleted_at IS NULL AND t.archived_at IS NULL
+                 GROUP BY l.id ORDER BY l.position, l.id",
+            )?
+            .query_map([], |row| {
+                Ok(ListRate { list_id: row.get(0)?, name: row.get(1)?, completed: row.get(2)?, total: row.get(3)? })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Stats { days: day_counts, weeks: week_counts, streaks, lists })
+    }
+
+    /// Live todos and notes matching `query`, best first.
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<SearchHit>> {
+        let Some(fts_query) = search::fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.conn();
+
+        let mut todos = conn
+            .prepare(
+                "SELECT t.id, t.list_id, l.name, snippet(todos_fts, 0, ?2, ?3, '…', 12), todos_fts.rank
+                 FROM todos_fts
+                 JOIN todos t ON t.id = todos_fts.rowid
+                 JOIN lists l ON l.id = t.list_id
+                 WHERE todos_fts MATCH ?1 AND t.deleted_at IS NULL AND t.archived_at IS NULL
+                 ORDER BY todos_fts.rank
+                 LIMIT ?4",
+            )?
+            .query_map(
+                params![fts_query, search::HIGHLIGHT_START, search::HIGHLIGHT_END, limit],
+                |row| {
+                    Ok(SearchHit {
+                        kind: HitKind::Todo,
+                        id: row.get(0)?,
+                        list_id: row.get(1)?,
+                        list_name: row.get(2)?,
+                        snippet: row.get(3)?,
+                        path: Vec::new(),
+                        rank: row.get(4)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for hit in &mut todos {
+            hit.path = ancestor_texts(&conn, hit.id)?;
+        }
+
+        let notes = conn
+            .prepare(
+                "SELECT n.id, n.title, snippet(notes_fts, -1, ?2, ?3, '…', 12), notes_fts.rank
+                 FROM notes_fts
+                 JOIN notes n ON n.id = notes_fts.rowid
+                 WHERE notes_fts MATCH ?1
+                 ORDER BY notes_fts.rank
+                 LIMIT ?4",
+            )?
+            .query_map(
+                params![fts_query, search::HIGHLIGHT_START, search::HIGHLIGHT_END, limit],
+                |row| {
+                    Ok(SearchHit {
+                        kind: HitKind::Note,
+                        id: row.get(0)?,
+                        list_id: None,
+                        list_name: None,
+                        snippet: row.get(2)?,
+                        path: vec![row.get(1)?],
+                        rank: row.get(3)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut hits: Vec<SearchHit> = todos.into_iter().chain(notes).collect();
+        hits.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+        hits.truncate(limit as usize);
+        Ok(hits)
+    }
+
+    pub fn get_notes(&self) -> Result<Vec<NoteSummary>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare(
+            "SELECT n.id, n.title, n.position, n.updated_at, w.note_id IS NOT NULL
+             FROM notes n LEFT JOIN note_windows w ON w.note_id = n.id
+             ORDER BY n.position, n.id",
+        )?;
+        let notes = stmt
+            .query_map([], |row| {
+                Ok(NoteSummary {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    position: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    windowed: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(notes)
+    }
+
+    pub fn get_note(&self, id: u32) -> Result<Note> {
+        let conn = self.conn();
+
+        conn.query_row(
+            "SELECT id, title, COALESCE(content, ''), updated_at FROM notes WHERE id = ?1",
+            params![id],
+            |row| Ok(Note { id: row.get(0)?, title: row.get(1)?, content: row.get(2)?, updated_at: row.get(3)? }),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Note {}", id)))
+    }
+
+    /// Overwrites a note's text, keeping the old text as a revision if the
+    /// last one is older than `revisions::SNAPSHOT_INTERVAL_MS`.
+    pub fn save_note(&self, id: u32, content: String) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+
+        let now = now_millis();
+        snapshot_note(&tx, id, now, false)?;
+        tx.execute(
+            "UPDATE notes SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![content, now, id],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Revisions of a note, newest first.
+    pub fn get_note_revisions(&self, note_id: u32) -> Result<Vec<NoteRevision>> {
+        let conn = self.conn();
+        expect_note(&conn, note_id)?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, note_id, created_at, content FROM note_revisions
+             WHERE note_id = ?1 ORDER BY created_at DESC, id DESC",
+        )?;
+        let revisions = stmt
+            .query_map(params![note_id], |row| {
+                let content: String = row.get(3)?;
+                Ok(NoteRevision {
+                    id: row.get(0)?,
+                    note_id: row.get(1)?,
+                    created_at: row.get(2)?,
+                    preview: revisions::preview(&content),
+                    length: content.chars().count() as u32,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(revisions)
+    }
+
+    /// How the note's current text differs from a revision of it.
+    pub fn diff_note_revision(&self, id: u32) -> Result<Vec<DiffLine>> {
+        let conn = self.conn();
+
+        let (old, current): (String, String) = conn
+            .query_row(
+                "SELECT r.content, COALESCE(n.content, '') FROM note_revisions r
+                 JOIN notes n ON n.id = r.note_id WHERE r.id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("Revision {}", id)))?;
+
+        Ok(revisions::diff_lines(&old, &current))
+    }
+
+    /// Puts a revision's text back, first keeping the current text as a
+    /// revision so the restore can itself be reverted. Returns the note's id.
+    pub fn restore_note_revision(&self, id: u32) -> Result<u32> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+
+        let (note_id, content): (u32, String) = tx
+            .query_row("SELECT note_id, content FROM note_revisions WHERE id = ?1", params![id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("Revision {}", id)))?;
+
+        let now = now_millis();
+        snapshot_note(&tx, note_id, now, true)?;
+        tx.execute(
+            "UPDATE notes SET content = ?1, updated_at = ?2 WHERE id = ?3",
+            params![content, now, note_id],
+        )?;
+
+        tx.commit()?;
+        Ok(note_id)
+    }
+
+    /// Appends a new, empty note and returns its id. A blank title becomes
+    /// "Untitled".
+    pub fn create_note(&self, title: String) -> Result<u32> {
+        let title = note_title(&title).unwrap_or_else(|_| "Untitled".to_string());
+        let conn = self.conn();
+
+        conn.execute(
+            "INSERT INTO notes (title, content, position, updated_at)
+             VALUES (?1, '', (SELECT COALESCE(MAX(position), -1) + 1 FROM notes), ?2)",
+            params![title, now_millis()],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u32)
+    }
+
+    pub fn rename_note(&self, id: u32, title: String) -> Result<()> {
+        let title = note_title(&title)?;
+        let conn = self.conn();
+
+        let updated = conn.execute("UPDATE notes SET title = ?1 WHERE id = ?2", params![title, id])?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!("Note {}", id)));
+        }
+        Ok(())
+    }
+
+    pub fn move_note(&self, id: u32, target_position: i32) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+
+        let position: i32 = tx
+            .query_row("SELECT position FROM notes WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("Note {}", id)))?;
+
+        tx.execute("UPDATE notes SET position = position - 1 WHERE position > ?1", params![position])?;
+        tx.execute("UPDATE notes SET position = position + 1 WHERE position >= ?1", params![target_position])?;
+        tx.execute("UPDATE notes SET position = ?1 WHERE id = ?2", params![target_position, id])?;
+        compact_note_positions(&tx)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Deletes a note for good, notes are not journaled. The last remaining
+    /// note cannot be deleted.
+    pub fn delete_note(&self, id: u32) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+
+        expect_note(&tx, id)?;
+        let note_count: i32 = tx.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
+        if note_count <= 1 {
+            return Err(AppError::ConstraintViolation("Cannot delete the only note".to_string()));
+        }
+        tx.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
+        compact_note_positions(&tx)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// The note shown in the main window: the last one selected, or the first one.
+    pub fn active_note(&self) -> Result<u32> {
+        let conn = self.conn();
+
+        let saved = get_setting(&conn, "active_note_id")?.and_then(|v| v.parse::<u32>().ok());
+        let id = conn.query_row(
+            "SELECT id FROM notes ORDER BY id = ?1 DESC, position, id LIMIT 1",
+            params![saved],
+            |row| row.get(0),
+        )?;
+
+        Ok(id)
+    }
+
+    pub fn set_active_note(&self, id: u32) -> Result<()> {
+        let conn = self.conn();
+        expect_note(&conn, id)?;
+        set_setting(&conn, "active_note_id", &id.to_string())
+    }
+
+    /// Gives a note its own sticky window, keeping the geometry it had if
+    /// it already has one.
+    pub fn open_note_window(&self, id: u32) -> Result<WindowState> {
+        {
+            let conn = self.conn();
+            expect_note(&conn, id)?;
+            conn.execute("INSERT OR IGNORE INTO note_windows (note_id) VALUES (?1)", params![id])?;
+        }
+        self.load_note_window_state(id)?
+            .ok_or_else(|| AppError::not_found(format!("Window of note {}", id)))
+    }
+
+    /// Forgets a note's window, so it is not reopened at startup.
+    pub fn close_note_window(&self, id: u32) -> Result<()> {
+        let conn = self.conn();
+        conn.execute("DELETE FROM note_windows WHERE note_id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Notes with a window of their own and where it was.
+    pub fn note_windows(&self) -> Result<Vec<(u32, WindowState)>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare("SELECT note_id, width, height, x, y, pinned FROM note_windows ORDER BY note_id")?;
+        let windows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    WindowState {
+                        width: row.get(1)?,
+                        height: row.get(2)?,
+                        x: row.get(3)?,
+                        y: row.get(4)?,
+                        pinned: row.get::<_, i32>(5)? != 0,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(windows)
+    }
+
+    /// Like `save_window_state`, for a note's own window. Does nothing once
+    /// the window was closed.
+    pub fn save_note_window_state(&self, id: u32, width: f64, height: f64, x: f64, y: f64, pinned: bool) -> Result<()> {
+        let conn = self.conn();
+
+        conn.execute(
+            "UPDATE note_windows SET width = ?1, height = ?2, x = ?3, y = ?4, pinned = ?5 WHERE note_id = ?6",
+            params![width, height, x, y, pinned, id],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn load_note_window_state(&self, id: u32) -> Result<Option<WindowState>> {
+        let conn = self.conn();
+
+        let state = conn
+            .query_row(
+                "SELECT width, height, x, y, pinned FROM note_windows WHERE note_id = ?1",
+                params![id],
+                |row| {
+                    Ok(WindowState {
+                        width: row.get(0)?,
+                        height: row.get(1)?,
+                        x: row.get(2)?,
+                        y: row.get(3)?,
+                        pinned: row.get::<_, i32>(4)? != 0,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(state)
+    }
+
+    /// Live items of one list, siblings in display order.
+    pub fn get_todos(&self, list_id: u32) -> Result<Vec<TodoItem>> {
+        let conn = self.conn();
+
+        let order = match sort_mode(&conn)? {
+            SortMode::Manual => "position ASC",
+            SortMode::Priority => "priority DESC, position ASC",
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM todos WHERE list_id = ?1 AND deleted_at IS NULL AND archived_at IS NULL ORDER BY {}",
+            TODO_COLUMNS, order
+        ))?;
+        let todo_iter = stmt.query_map(params![list_id], TodoItem::from_row)?;
+
+        let mut tags = todo_tags(&conn)?;
+        let mut todos = Vec::new();
+        for todo in todo_iter {
+            let mut todo = todo?;
+            todo.tags = tags.remove(&todo.id).unwrap_or_default();
+            todos.push(todo);
+        }
+
+        Ok(todos)
+    }
+
+    /// A list as a Markdown checklist, siblings in manual order whatever
+    /// the sort mode.
+    pub fn export_markdown(&self, list_id: u32) -> Result<String> {
+        let conn = self.conn();
+        expect_list(&conn, list_id)?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM todos WHERE list_id = ?1 AND deleted_at IS NULL AND archived_at IS NULL ORDER BY position, id",
+            TODO_COLUMNS
+        ))?;
+        let todos = stmt
+            .query_map(params![list_id], TodoItem::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(checklist::to_markdown(&todos))
+    }
+
+    /// All tags in use, alphabetically.
+    pub fn get_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare(
+            "SELECT g.name, COUNT(t.id) FROM tags g
+             JOIN todo_tags tt ON tt.tag_id = g.id
+             LEFT JOIN todos t ON t.id = tt.todo_id AND t.deleted_at IS NULL AND t.archived_at IS NULL
+             GROUP BY g.id
+             HAVING COUNT(t.id) > 0
+             ORDER BY g.name",
+        )?;
+        let tags = stmt
+            .query_map([], |row| Ok(Tag { name: row.get(0)?, count: row.get(1)? }))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(tags)
+    }
+
+    pub fn save_todo(&self, list_id: u32, text: String) -> Result<u32> {
+        let (text, priority) = markers::extract_priority(&text);
+
+        self.journaled("Add item", |tx| {
+            expect_list(tx, list_id)?;
+
+            // Get max position to append to end
+            let max_pos: rusqlite::Result<i32> = tx.query_row(
+                "SELECT COALESCE(MAX(position), -1) FROM todos
+                 WHERE list_id = ?1 AND parent_id IS NULL AND deleted_at IS NULL AND archived_at IS NULL",
+                params![list_id],
+                |row| row.get(0),
+            );
+            let position = max_pos.unwrap_or(-1) + 1;
+
+            println!("[DB] Creating new todo with position: {}", position);
+
+            tx.execute(
+                "INSERT INTO todos (list_id, text, completed, parent_id, position, priority)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![list_id, text, false, None::<u32>, position, priority.unwrap_or_default()],
+            )?;
+
+            let id = tx.last_insert_rowid() as u32;
+            sync_tags(tx, id, &text)?;
+            println!("[DB] Created todo id={} at position={}", id, position);
+            Ok(id)
+        })
+    }
+
+    /// Adds a Markdown checklist as items of `list_id`, appended after the
+    /// children of `parent_id` (or the top level), keeping its nesting and
+    /// check marks. Returns the ids of the top-level items added.
+    pub fn import_markdown(&self, list_id: u32, parent_id: Option<u32>, markdown: &str) -> Result<Vec<u32>> {
+        let items = checklist::parse(markdown);
+        if items.is_empty() {
+            return Err(AppError::InvalidInput("No checklist items found".to_string()));
+        }
+
+        self.journaled("Import checklist", |tx| {
+            expect_list(tx, list_id)?;
+            if let Some(parent_id) = parent_id {
+                let (parent_list, _, _) = todo_location(tx, parent_id)?;
+                if parent_list != list_id {
+                    return Err(AppError::InvalidInput("Parent item is in another list".to_string()));
+                }
+            }
+
+            let start: i32 = tx.query_row(
+                "SELECT COALESCE(MAX(position), -1) + 1 FROM todos
+                 WHERE list_id = ?1 AND parent_id IS ?2 AND deleted_at IS NULL AND archived_at IS NULL",
+                params![list_id, parent_id],
+                |row| row.get(0),
+            )?;
+
+            let mut ids = Vec::with_capacity(items.len());
+            for (offset, item) in items.iter().enumerate() {
+                ids.push(insert_outline(tx, list_id, parent_id, start + offset as i32, item)?);
+            }
+            if parent_id.is_some() {
+                update_ancestors(tx, ids[0])?;
+            }
+            println!("[DB] Imported {} top-level items into list {}", ids.len(), list_id);
+            Ok(ids)
+        })
+    }
+
+    pub fn update_todo(&self, id: u32, completed: bool) -> Result<()> {
+        let label = if completed { "Complete item" } else { "Uncheck item" };
+        self.journaled(label, |tx| {
+            println!("[DB] update_todo: id={}, completed={}", id, completed);
+            if completed {
+                complete_and_recur(tx, id)?;
+            } else {
+                set_completed(tx, id, false)?;
+                log_event(tx, id, EventKind::Uncompleted, None)?;
+            }
+            Ok(())
+        })?;
+        println!("[DB] update_todo transaction committed");
+
+        Ok(())
+    }
+
+    /// Replaces the text of an item. A priority marker in the new text sets
+    /// the priority, otherwise it is left unchanged.
+    pub fn update_todo_text(&self, id: u32, text: String) -> Result<()> {
+        let (text, priority) = markers::extract_priority(&text);
+
+        self.journaled("Edit text", |tx| {
+            let updated = tx.execute(
+                "UPDATE todos SET text = ?1, priority = COALESCE(?2, priority) WHERE id = ?3",
+                params![text, priority, id],
+            )?;
+            expect_todo(updated, id)?;
+
+            sync_tags(tx, id, &text)
+        })
+    }
+
+    /// Moves an item to the trash. With `DeleteMode::Cascade` its live
+    /// descendants go with it and are restored together.
+    pub fn delete_todo(&self, id: u32, mode: DeleteMode) -> Result<()> {
+        self.journaled("Delete item", |tx| {
+            let (list_id, parent_id, position) = todo_location(tx, id)?;
+            let deleted_at = now_millis();
+
+            match mode {
+                DeleteMode::Cascade => {
+                    let trashed = tx.execute(
+                        "WITH RECURSIVE subtree(id) AS (
+                            SELECT ?1
+                            UNION ALL
+                            SELECT t.id FROM todos t
+                            JOIN subtree s ON t.parent_id = s.id
+                            WHERE t.deleted_at IS NULL AND t.archived_at IS NULL
+                        )
+                        UPDATE todos SET deleted_at = ?2 WHERE id IN subtree",
+                        params![id, deleted_at],
+                    )?;
+                    println!("[DB] delete_todo: moved {} items to trash", trashed);
+                    log_event(tx, id, EventKind::Deleted, Some(trashed as i64))?;
+                }
+                DeleteMode::Reparent => {
+                    let child_count: i32 = tx.query_row(
+                        "SELECT COUNT(*) FROM todos WHERE parent_id = ? AND deleted_at IS NULL AND archived_at IS NULL",
+                        params![id],
+                        |row| row.get(0),
+                    )?;
+
+                    // Make room after the deleted item, then slot the children in.
+                    tx.execute(
+                        "UPDATE todos SET position = position + ?1
+                         WHERE list_id = ?4 AND parent_id IS ?2 AND position > ?3 AND deleted_at IS NULL AND archived_at IS NULL",
+                        params![child_count, parent_id, position, list_id],
+                    )?;
+                    tx.execute(
+                        "WITH ordered AS (
+                            SELECT id, ROW_NUMBER() OVER (ORDER BY position, id) AS rn
+                            FROM todos WHERE parent_id = ?1 AND deleted_at IS NULL AND archived_at IS NULL
+                        )
+                        UPDATE todos SET parent_id = ?2, position = ?3 + ordered.rn
+                        FROM ordered WHERE todos.id = ordered.id",
+                        params![id, parent_id, position],
+                    )?;
+                    tx.execute(
+                        "UPDATE todos SET deleted_at = ?1 WHERE id = ?2",
+                        params![deleted_at, id],
+                    )?;
+                    log_event(tx, id, EventKind::Deleted, Some(1))?;
+                }
+            }
+
+            compact_positions(tx, list_id, parent_id)?;
+            update_ancestors(tx, id)?;
+
+            Ok(())
+        })
+    }
+
+    pub fn get_trash(&self) -> Result<Vec<TrashEntry>> {
+        let conn = self.conn();
+
+        // A trashed item is listed on its own unless its parent went to the
+        // trash in the same batch, in which case it is part of that entry.
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE roots AS (
+                SELECT t.id, t.text, t.parent_id, t.deleted_at FROM todos t
+                LEFT JOIN todos p ON p.id = t.parent_id
+                WHERE t.deleted_at IS NOT NULL
+                  AND (p.id IS NULL OR p.deleted_at IS NOT t.deleted_at)
+            ),
+            batch(root, id) AS (
+                SELECT id, id FROM roots
+                UNION ALL
+                SELECT b.root, t.id FROM todos t
+                JOIN batch b ON t.parent_id = b.id
+                JOIN roots r ON r.id = b.root
+                WHERE t.deleted_at = r.deleted_at
+            )
+            SELECT r.id, r.text, r.parent_id, r.deleted_at,
+                   (SELECT COUNT(*) FROM batch b WHERE b.root = r.id),
+                   (SELECT l.name FROM todos t JOIN lists l ON l.id = t.list_id WHERE t.id = r.id)
+            FROM roots r
+            ORDER BY r.deleted_at DESC",
+        )?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(TrashEntry {
+                    id: row.get(0)?,
+                    text: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    list_name: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                    deleted_at: row.get(3)?,
+                    item_count: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(entries)
+    }
+
+    /// Brings a trash entry back to its original parent and position. If the
+    /// parent is gone or itself in the trash, the item goes to the end of the
+    /// root list instead.
+    pub fn restore_todo(&self, id: u32) -> Result<()> {
+        self.journaled("Restore item", |tx| restore_subtree(tx, id, "deleted_at", "the trash"))
+    }
+
+    /// Moves a completed item and its subtree to the archive.
+    pub fn archive_todo(&self, id: u32) -> Result<()> {
+        self.journaled("Archive item", |tx| {
+            let completed: bool = tx
+                .query_row(
+                    "SELECT completed FROM todos WHERE id = ?1 AND deleted_at IS NULL AND archived_at IS NULL",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| AppError::not_found(format!("Todo {}", id)))?;
+            if !completed {
+                return Err(AppError::InvalidInput("Only completed items can be archived".to_string()));
+            }
+            let archived = archive_subtree(tx, id, now_millis())?;
+            println!("[DB] archive_todo: archived {} items", archived);
+            Ok(())
+        })
+    }
+
+    /// Archived subtrees, most recent first. Like the trash, items archived
+    /// together with their parent belong to the parent's entry.
+    pub fn get_archive(&self) -> Result<Vec<ArchiveEntry>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.text, l.name, t.archived_at FROM todos t
+             LEFT JOIN todos p ON p.id = t.parent_id
+             LEFT JOIN lists l ON l.id = t.list_id
+             WHERE t.archived_at IS NOT NULL AND t.deleted_at IS NULL
+               AND (p.id IS NULL OR p.archived_at IS NOT t.archived_at)
+             ORDER BY t.archived_at DESC, t.id",
+        )?;
+        let mut entries = stmt
+            .query_map([], |row| {
+                Ok(ArchiveEntry {
+                    id: row.get(0)?,
+                    text: row.get(1)?,
+                    list_name: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                    archived_at: row.get(3)?,
+                    items: Vec::new(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut items_stmt = conn.prepare(
+            "WITH RECURSIVE batch(id, depth, sort_key) AS (
+                SELECT id, 1, printf('%08d', position) FROM todos
+                WHERE parent_id = ?1 AND archived_at = ?2 AND deleted_at IS NULL
+                UNION ALL
+                SELECT t.id, b.depth + 1, b.sort_key || '/' || printf('%08d', t.position) FROM todos t
+                JOIN batch b ON t.parent_id = b.id
+                WHERE t.archived_at = ?2 AND t.deleted_at IS NULL
+            )
+            SELECT t.id, t.text, b.depth, t.completed FROM batch b JOIN todos t ON t.id = b.id
+            ORDER BY b.sort_key",
+        )?;
+        for entry in &mut entries {
+            entry.items = items_stmt
+                .query_map(params![entry.id, entry.archived_at], |row| {
+                    Ok(ArchivedItem { id: row.get(0)?, text: row.get(1)?, depth: row.get(2)?, completed: row.get(3)? })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+        }
+
+        Ok(entries)
+    }
+
+    pub fn restore_archived(&self, id: u32) -> Result<()> {
+        self.journaled("Restore from archive", |tx| restore_subtree(tx, id, "archived_at", "the archive"))
+    }
+
+    /// Permanently deletes an archive entry and everything below it.
+    pub fn purge_archived(&self, id: u32) -> Result<()> {
+        self.journaled("Delete forever", |tx| {
+            let purged = purge(tx, "SELECT id FROM todos WHERE id = ?1 AND archived_at IS NOT NULL", params![id])?;
+            if purged == 0 {
+                return Err(AppError::not_found(format!("Todo {} in the archive", id)));
+            }
+            Ok(())
+        })
+    }
+
+    /// Archives completed items that have been done for longer than the
+    /// configured number of days, each with its subtree. Only the topmost
+    /// completed item of a branch is picked, so a finished project goes as
+    /// one entry. Returns the number of entries created.
+    pub fn run_auto_archive(&self) -> Result<usize> {
+        let days = self.auto_archive_days()?;
+        if days == 0 {
+            return Ok(0);
+        }
+        let cutoff = now_millis() - i64::from(days) * 24 * 60 * 60 * 1000;
+
+        // Picked in the same transaction, so an item unchecked in between
+        // is never archived
+        let archived = self.unjournaled(|tx| {
+            let due = tx
+                .prepare(
+                    "SELECT t.id FROM todos t LEFT JOIN todos p ON p.id = t.parent_id
+                     WHERE t.completed = 1 AND t.completed_at < ?1
+                       AND t.deleted_at IS NULL AND t.archived_at IS NULL
+                       AND (p.id IS NULL OR p.completed = 0)",
+                )?
+                .query_map(params![cutoff], |row| row.get::<_, u32>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let archived_at = now_millis();
+            for id in &due {
+                archive_subtree(tx, *id, archived_at)?;
+            }
+            Ok(due.len())
+        })?;
+        if archived > 0 {
+            println!("[DB] Auto-archived {} items done for more than {} days", archived, days);
+        }
+
+        Ok(archived)
+    }
+
+    /// 0 turns auto-archiving off.
+    pub fn auto_archive_days(&self) -> Result<u32> {
+        let conn = self.conn();
+        Ok(get_setting(&conn, "auto_archive_days")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0))
+    }
+
+    pub fn set_auto_archive_days(&self, days: u32) -> Result<()> {
+        set_setting(&self.conn(), "auto_archive_days", &days.to_string())?;
+        self.run_auto_archive()?;
+        Ok(())
+    }
+
+    /// Permanently deletes a trash entry and everything below it.
+    pub fn purge_todo(&self, id: u32) -> Result<()> {
+        self.journaled("Delete forever", |tx| {
+            let purged = purge(tx, "SELECT id FROM todos WHERE id = ?1 AND deleted_at IS NOT NULL", params![id])?;
+            if purged == 0 {
+                return Err(AppError::not_found(format!("Todo {} in the trash", id)));
+            }
+            Ok(())
+        })
+    }
+
+    pub fn empty_trash(&self) -> Result<usize> {
+        self.journaled("Empty trash", |tx| {
+            purge(tx, "SELECT id FROM todos WHERE deleted_at IS NOT NULL", [])
+        })
+    }
+
+    /// Purges trash entries older than the configured retention. Returns the
+    /// number of rows removed.
+    pub fn purge_expired_trash(&self) -> Result<usize> {
+        let conn = self.conn();
+
+        let days = trash_retention_days(&conn)?;
+        let cutoff = now_millis() - i64::from(days) * 24 * 60 * 60 * 1000;
+        let purged = purge(&conn, "SELECT id FROM todos WHERE deleted_at < ?1", params![cutoff])?;
+        if purged > 0 {
+            println!("[DB] Purged {} trashed todos older than {} days", purged, days);
+        }
+
+        Ok(purged)
+    }
+
+    pub fn trash_retention_days(&self) -> Result<u32> {
+        trash_retention_days(&self.conn())
+    }
+
+    pub fn set_trash_retention_days(&self, days: u32) -> Result<()> {
+        set_setting(&self.conn(), "trash_retention_days", &days.to_string())?;
+        self.purge_expired_trash()?;
+        Ok(())
+    }
+
+    pub fn sort_mode(&self) -> Result<SortMode> {
+        sort_mode(&self.conn())
+    }
+
+    pub fn set_sort_mode(&self, mode: SortMode) -> Result<()> {
+        let value = match mode {
+            SortMode::Manual => "manual",
+            SortMode::Priority => "priority",
+        };
+        set_setting(&self.conn(), "sort_mode", value)
+    }
+
+    pub fn note_view(&self) -> Result<NoteView> {
+        Ok(match get_setting(&self.conn(), "note_view")?.as_deref() {
+            Some("preview") => NoteView::Preview,
+            Some("split") => NoteView::Split,
+            _ => NoteView::Edit,
+        })
+    }
+
+    pub fn set_note_view(&self, view: NoteView) -> Result<()> {
+        let value = match view {
+            NoteView::Edit => "edit",
+            NoteView::Preview => "preview",
+            NoteView::Split => "split",
+        };
+        set_setting(&self.conn(), "note_view", value)
+    }
+
+    pub fn tab(&self) -> Result<Tab> {
+        Ok(match get_setting(&self.conn(), "tab")?.as_deref() {
+            Some("notes") => Tab::Notes,
+            _ => Tab::Todos,
+        })
+    }
+
+    pub fn set_tab(&self, tab: Tab) -> Result<()> {
+        let value = match tab {
+            Tab::Todos => "todos",
+            Tab::Notes => "notes",
+        };
+        set_setting(&self.conn(), "tab", value)
+    }
+
+    /// Moves an item within its list. Dropping it under a parent that lives
+    /// in another list takes the whole subtree to that list.
+    pub fn move_todo(&self, id: u32, target_parent_id: Option<u32>, target_position: i32) -> Result<()> {
+        self.journaled("Move item", |tx| {
+            // 1. Get current state
+            let (current_list_id, current_parent_id, current_position) = todo_location(tx, id)?;
+            let target_list_id = match target_parent_id {
+                Some(pid) => todo_location(tx, pid)?.0,
+                None => current_list_id,
+            };
+
+            // Refuse to move an item into its own subtree, it would detach the whole branch
+            if let Some(target) = target_parent_id {
+                let creates_cycle: bool = tx.query_row(
+                    "WITH RECURSIVE ancestors(id) AS (
+                        SELECT ?1
+                        UNION ALL
+                        SELECT t.parent_id FROM todos t
+                        JOIN ancestors a ON t.id = a.id
+                        WHERE t.parent_id IS NOT NULL
+                    )
+                    SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?2)",
+                    params![target, id],
+                    |row| row.get(0),
+                )?;
+                if creates_cycle {
+                    return Err(AppError::ConstraintViolation(
+                        "Cannot move an item into its own subtree".to_string(),
+                    ));
+                }
+            }
+
+            // 2. Remove from old list (shift items up)
+            if let Some(pid) = current_parent_id {
+                tx.execute(
+                    "UPDATE todos SET position = position - 1 WHERE parent_id = ? AND position > ? AND deleted_at IS NULL AND archived_at IS NULL",
+                    params![pid, current_position],
+                )?;
+            } else {
+                tx.execute(
+                    "UPDATE todos SET position = position - 1 WHERE list_id = ? AND parent_id IS NULL AND position > ? AND deleted_at IS NULL AND archived_at IS NULL",
+                    params![current_list_id, current_position],
+                )?;
+            }
+
+            // 3. Make space in new list (shift items down)
+            if let Some(pid) = target_parent_id {
+                tx.execute(
+                    "UPDATE todos SET position = position + 1 WHERE parent_id = ? AND position >= ? AND deleted_at IS NULL AND archived_at IS NULL",
+                    params![pid, target_position],
+                )?;
+            } else {
+                tx.execute(
+                    "UPDATE todos SET position = position + 1 WHERE list_id = ? AND parent_id IS NULL AND position >= ? AND deleted_at IS NULL AND archived_at IS NULL",
+                    params![target_list_id, target_position],
+                )?;
+            }
+
+            // 4. Update the item itself
+            tx.execute(
+                "UPDATE todos SET parent_id = ?, position = ? WHERE id = ?",
+                params![target_parent_id, target_position, id],
+            )?;
+
+            if target_list_id != current_list_id {
+                tx.execute(
+                    "WITH RECURSIVE subtree(id) AS (
+                        SELECT ?1
+                        UNION ALL
+                        SELECT t.id FROM todos t JOIN subtree s ON t.parent_id = s.id
+                    )
+                    UPDATE todos SET list_id = ?2 WHERE id IN subtree",
+                    params![id, target_list_id],
+                )?;
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn set_todo_count(&self, id: u32, count: Option<i32>) -> Result<()> {
+        self.journaled("Set count", |tx| {
+            let current_count = count.unwrap_or(0);
+
+            let updated = tx.execute(
+                "UPDATE todos SET target_count = ?1, current_count = ?2 WHERE id = ?3",
+                params![count, current_count, id],
+            )?;
+
+            expect_todo(updated, id)
+        })
+    }
+
+    /// Sets or clears the due and scheduled dates (`YYYY-MM-DD`) of an item.
+    pub fn set_todo_dates(&self, id: u32, due_at: Option<String>, scheduled_for: Option<String>) -> Result<()> {
+        let due_at = due_at.map(|d| parse_date(&d)).transpose()?;
+        let scheduled_for = scheduled_for.map(|d| parse_date(&d)).transpose()?;
+
+        self.journaled("Set dates", |tx| {
+            let updated = tx.execute(
+                "UPDATE todos SET due_at = ?1, scheduled_for = ?2 WHERE id = ?3",
+                params![due_at, scheduled_for, id],
+            )?;
+
+            expect_todo(updated, id)
+        })
+    }
+
+    /// Sets the Markdown description of an item. Blank text clears it.
+    pub fn set_todo_description(&self, id: u32, description: Option<String>) -> Result<()> {
+        let description = description.filter(|d| !d.trim().is_empty());
+
+        self.journaled("Edit description", |tx| {
+            let updated = tx.execute(
+                "UPDATE todos SET description = ?1 WHERE id = ?2",
+                params![description, id],
+            )?;
+
+            expect_todo(updated, id)
+        })
+    }
+
+    /// Sets or clears the rule an item repeats by.
+    pub fn set_todo_recurrence(&self, id: u32, rule: Option<RecurrenceRule>) -> Result<()> {
+        if let Some(rule) = &rule {
+            rule.validate()?;
+        }
+
+        self.journaled("Set repeat", |tx| {
+            let updated = tx.execute(
+                "UPDATE todos SET recurrence = ?1 WHERE id = ?2",
+                params![rule.as_ref().map(RecurrenceRule::to_json), id],
+            )?;
+
+            expect_todo(updated, id)
+        })
+    }
+
+    pub fn set_todo_priority(&self, id: u32, priority: Priority) -> Result<()> {
+        self.journaled("Set priority", |tx| {
+            let updated = tx.execute(
+                "UPDATE todos SET priority = ?1 WHERE id = ?2",
+                params![priority, id],
+            )?;
+
+            expect_todo(updated, id)
+        })
+    }
+
+    pub fn decrement_todo(&self, id: u32) -> Result<()> {
+        self.journaled("Count down", |tx| {
+            // Decrement count
+            let decremented = tx.execute(
+                "UPDATE todos SET current_count = current_count - 1 WHERE id = ? AND current_count > 0",
+                params![id],
+            )?;
+
+            // Check if reached 0
+            let current_count: i32 = tx
+                .query_row(
+                    "SELECT current_count FROM todos WHERE id = ?",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| AppError::not_found(format!("Todo {}", id)))?;
+
+            if decremented > 0 {
+                log_event(tx, id, EventKind::Decremented, Some(i64::from(current_count)))?;
+            }
+
+            // Only the step that reaches zero completes the item; counting
+            // down a finished countdown or a plain item changes nothing
+            if decremented > 0 && current_count == 0 {
+                // Mark as completed and trigger cascade
+                complete_and_recur(tx, id)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn reset_all_todos(&self, list_id: u32) -> Result<()> {
+        self.journaled("Reset all", |tx| {
+            expect_list(tx, list_id)?;
+            reset_todos(tx, list_id, None)
+        })
+    }
+
+    /// Runs a view query over all lists. Items come grouped by list, in the
+    /// order `get_todos` uses within a list.
+    pub fn run_query(&self, query: &str) -> Result<QueryResult> {
+        let query = Query::parse(query)?;
+        let (condition, values) = query.to_sql(chrono::Local::now().date_naive());
+        let conn = self.conn();
+
+        let matches = conn
+            .prepare(&format!("SELECT t.id FROM todos t WHERE {}", condition))?
+            .query_map(rusqlite::params_from_iter(values.iter()), |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<u32>>>()?;
+
+        let order = match sort_mode(&conn)? {
+            SortMode::Manual => "position ASC",
+            SortMode::Priority => "priority DESC, position ASC",
+        };
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE context(id) AS (
+                SELECT t.id FROM todos t WHERE {}
+                UNION
+                SELECT p.parent_id FROM todos p JOIN context c ON p.id = c.id
+                WHERE p.parent_id IS NOT NULL
+            )
+            SELECT {} FROM todos
+            WHERE id IN context AND deleted_at IS NULL AND archived_at IS NULL
+            ORDER BY (SELECT position FROM lists WHERE lists.id = todos.list_id), list_id, {}",
+            condition, TODO_COLUMNS, order
+        ))?;
+        let todo_iter = stmt.query_map(rusqlite::params_from_iter(values.iter()), TodoItem::from_row)?;
+
+        let mut tags = todo_tags(&conn)?;
+        let mut todos = Vec::new();
+        for todo in todo_iter {
+            let mut todo = todo?;
+            todo.tags = tags.remove(&todo.id).unwrap_or_default();
+            todos.push(todo);
+        }
+
+        Ok(QueryResult { todos, matches })
+    }
+
+    pub fn get_views(&self) -> Result<Vec<SavedView>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare("SELECT id, name, query, layout, position FROM saved_views ORDER BY position, id")?;
+        let views = stmt
+            .query_map([], |row| {
+                Ok(SavedView {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    query: row.get(2)?,
+                    layout: row.get(3)?,
+                    position: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(views)
+    }
+
+    /// Saves a query under a new name, after checking that it parses.
+    pub fn create_view(&self, name: String, query: String, layout: ViewLayout) -> Result<u32> {
+        let name = view_name(&name)?;
+        Query::parse(&query)?;
+        let conn = self.conn();
+
+        conn.execute(
+            "INSERT INTO saved_views (name, query, layout, position)
+             VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position) + 1, 0) FROM saved_views))",
+            params![name, query.trim(), layout.as_str()],
+        )?;
+        Ok(conn.last_insert_rowid() as u32)
+    }
+
+    pub fn update_view(&self, id: u32, name: String, query: String, layout: ViewLayout) -> Result<()> {
+        let name = view_name(&name)?;
+        Query::parse(&query)?;
+
+        let updated = self.conn().execute(
+            "UPDATE saved_views SET name = ?1, query = ?2, layout = ?3 WHERE id = ?4",
+            params![name, query.trim(), layout.as_str(), id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!("View {}", id)));
+        }
+        Ok(())
+    }
+
+    pub fn delete_view(&self, id: u32) -> Result<()> {
+        let deleted = self.conn().execute("DELETE FROM saved_views WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(AppError::not_found(format!("View {}", id)));
+        }
+        Ok(())
+    }
+
+    pub fn get_reset_schedules(&self, list_id: u32) -> Result<Vec<ResetSchedule>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.list_id, s.todo_id, t.text, s.weekday, s.time, s.last_reset_at
+             FROM reset_schedules s LEFT JOIN todos t ON t.id = s.todo_id
+             WHERE s.list_id = ?1
+             ORDER BY s.time, s.id",
+        )?;
+        let schedules = stmt
+            .query_map(params![list_id], |row| {
+                Ok(ResetSchedule {
+                    id: row.get(0)?,
+                    list_id: row.get(1)?,
+                    todo_id: row.get(2)?,
+                    todo_text: row.get(3)?,
+                    frequency: ResetFrequency::from_weekday(row.get(4)?),
+                    time: row.get(5)?,
+                    last_reset_at: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(schedules)
+    }
+
+    /// Adds a schedule that first fires at its next occurrence, not for
+    /// occurrences that are already in the past.
+    pub fn add_reset_schedule(
+        &self,
+        list_id: u32,
+        todo_id: Option<u32>,
+        frequency: ResetFrequency,
+        time: String,
+    ) -> Result<u32> {
+        frequency.validate()?;
+        let time = schedule::parse_time(&time)?;
+
+        let conn = self.conn();
+        expect_list(&conn, list_id)?;
+        if let Some(todo_id) = todo_id {
+            if todo_location(&conn, todo_id)?.0 != list_id {
+                return Err(AppError::InvalidInput(format!("Todo {} is not in list {}", todo_id, list_id)));
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO reset_schedules (list_id, todo_id, weekday, time, last_reset_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![list_id, todo_id, frequency.weekday(), time, now_millis()],
+        )?;
+
+        Ok(conn.last_insert_rowid() as u32)
+    }
+
+    pub fn delete_reset_schedule(&self, id: u32) -> Result<()> {
+        let deleted = self.conn().execute("DELETE FROM reset_schedules WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(AppError::not_found(format!("Reset schedule {}", id)));
+        }
+        Ok(())
+    }
+
+    /// Applies every schedule whose reset time has passed since it last ran,
+    /// once, however many occurrences were missed. Returns how many ran.
+    pub fn run_due_resets(&self) -> Result<usize> {
+        let now = chrono::Local::now();
+        let now_ms = now.timestamp_millis();
+
+        let due: Vec<(u32, u32, Option<u32>)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(
+                "SELECT id, list_id, todo_id, weekday, time, last_reset_at FROM reset_schedules",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        (row.get::<_, u32>(0)?, row.get::<_, u32>(1)?, row.get::<_, Option<u32>>(2)?),
+                        ResetFrequency::from_weekday(row.get(3)?),
+                        row.get::<_, String>(4)?,
+                        row.get::<_, i64>(5)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows.into_iter()
+                .filter(|(_, frequency, time, last_reset_at)| {
+                    schedule::last_occurrence(frequency, time, now.naive_local())
+                        .is_some_and(|occurrence| occurrence > *last_reset_at)
+                })
+                .map(|(schedule, ..)| schedule)
+                .collect()
+        };
+
+        for &(id, list_id, todo_id) in &due {
+            // Recording the run in the same transaction is what keeps a
+            // reset from being applied twice
+            self.unjournaled(|tx| {
+                reset_todos(tx, list_id, todo_id)?;
+                tx.execute(
+                    "UPDATE reset_schedules SET last_reset_at = ?1 WHERE id = ?2",
+                    params![now_ms, id],
+                )?;
+                Ok(())
+            })?;
+            println!("[DB] Scheduled reset {} ran for list {} (subtree {:?})", id, list_id, todo_id);
+        }
+
+        Ok(due.len())
+    }
+
+    pub fn get_lists(&self) -> Result<Vec<List>> {
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare("SELECT id, name, position FROM lists ORDER BY position, id")?;
+        let lists = stmt
+            .query_map([], |row| Ok(List { id: row.get(0)?, name: row.get(1)?, position: row.get(2)? }))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(lists)
+    }
+
+    /// Appends a new, empty list and returns its id.
+    pub fn create_list(&self, name: String) -> Result<u32> {
+        let name = list_name(&name)?;
+
+        self.journaled("Add list", |tx| {
+            tx.execute(
+                "INSERT INTO lists (name, position)
+                 VALUES (?1, (SELECT COALESCE(MAX(position), -1) + 1 FROM lists))",
+                params![name],
+            )?;
+            Ok(tx.last_insert_rowid() as u32)
+        })
+    }
+
+    pub fn rename_list(&self, id: u32, name: String) -> Result<()> {
+        let name = list_name(&name)?;
+
+        self.journaled("Rename list", |tx| {
+            let updated = tx.execute("UPDATE lists SET name = ?1 WHERE id = ?2", params![name, id])?;
+            if updated == 0 {
+                return Err(AppError::not_found(format!("List {}", id)));
+            }
+            Ok(())
+        })
+    }
+
+    pub fn move_list(&self, id: u32, target_position: i32) -> Result<()> {
+        self.journaled("Move list", |tx| {
+            let position: i32 = tx
+                .query_row("SELECT position FROM lists WHERE id = ?1", params![id], |row| row.get(0))
+                .optional()?
+                .ok_or_else(|| AppError::not_found(format!("List {}", id)))?;
+
+            tx.execute("UPDATE lists SET position = position - 1 WHERE position > ?1", params![position])?;
+            tx.execute("UPDATE lists SET position = position + 1 WHERE position >= ?1", params![target_position])?;
+            tx.execute("UPDATE lists SET position = ?1 WHERE id = ?2", params![target_position, id])?;
+            compact_list_positions(tx)
+        })
+    }
+
+    /// Deletes a list together with all of its items, including the ones in
+    /// the trash. The last remaining list cannot be deleted.
+    pub fn delete_list(&self, id: u32) -> Result<()> {
+        self.journaled("Delete list", |tx| {
+            expect_list(tx, id)?;
+            let list_count: i32 = tx.query_row("SELECT COUNT(*) FROM lists", [], |row| row.get(0))?;
+            if list_count <= 1 {
+                return Err(AppError::ConstraintViolation("Cannot delete the only list".to_string()));
+            }
+
+            let purged = purge(tx, "SELECT id FROM todos WHERE list_id = ?1", params![id])?;
+            tx.execute("DELETE FROM lists WHERE id = ?1", params![id])?;
+            compact_list_positions(tx)?;
+            println!("[DB] delete_list: removed list {} with {} items", id, purged);
+
+            Ok(())
+        })
+    }
+
+    /// The list shown at startup: the last one selected, or the first one.
+    pub fn active_list(&self) -> Result<u32> {
+        let conn = self.conn();
+
+        let saved = get_setting(&conn, "active_list_id")?.and_then(|v| v.parse::<u32>().ok());
+        let id = conn.query_row(
+            "SELECT id FROM lists ORDER BY id = ?1 DESC, position, id LIMIT 1",
+            params![saved],
+            |row| row.get(0),
+        )?;
+
+        Ok(id)
+    }
+
+    pub fn set_active_list(&self, id: u32) -> Result<()> {
+        let conn = self.conn();
+        expect_list(&conn, id)?;
+        set_setting(&conn, "active_list_id", &id.to_string())
+    }
+
+    pub fn save_window_state(
+        &self,
+        width: f64,
+        height: f64,
+        x: f64,
+        y: f64,
+        pinned: bool,
+    ) -> Result<()> {
+        let conn = self.conn();
+
+        // Use INSERT OR REPLACE to upsert
+        conn.execute(
+            "INSERT OR REPLACE INTO window_state (id, width, height, x, y, pinned) VALUES (1, ?, ?, ?, ?, ?)",
+            params![width, height, x, y, if pinned { 1 } else { 0 }],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn load_window_state(&self) -> Result<Option<WindowState>> {
+        window_state(&self.conn())
+    }
+
+    /// Everything worth keeping, in the portable format of `backup.rs`.
+    pub fn export_backup(&self) -> Result<Backup> {
+        let mut conn = self.conn();
+        // One read transaction, so the parts agree with each other
+        let tx = conn.transaction()?;
+
+        let lists = tx
+            .prepare("SELECT id, name, position FROM lists ORDER BY position, id")?
+            .query_map([], |row| Ok(BackupList { id: row.get(0)?, name: row.get(1)?, position: row.get(2)? }))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let todos = tx
+            .prepare(
+                "SELECT id, list_id, parent_id, position, text, description, completed, completed_at,
+                        target_count, current_count, due_at, scheduled_for, priority, recurrence,
+                        deleted_at, archived_at
+                 FROM todos ORDER BY list_id, parent_id IS NOT NULL, parent_id, position, id",
+            )?
+            .query_map([], |row| {
+                Ok(BackupTodo {
+                    id: row.get(0)?,
+                    list_id: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    position: row.get(3)?,
+                    text: row.get(4)?,
+                    description: row.get(5)?,
+                    completed: row.get(6)?,
+                    completed_at: row.get(7)?,
+                    target_count: row.get(8)?,
+                    current_count: row.get(9)?,
+                    due_at: row.get(10)?,
+                    scheduled_for: row.get(11)?,
+                    priority: row.get(12)?,
+                    recurrence: row
+                        .get::<_, Option<String>>(13)?
+                        .as_deref()
+                        .and_then(RecurrenceRule::from_json),
+                    deleted_at: row.get(14)?,
+                    archived_at: row.get(15)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let events = tx
+            .prepare(
+                "SELECT todo_id, list_id, kind, value, created_at FROM todo_events
+                 WHERE undone = 0 ORDER BY created_at, id",
+            )?
+            .query_map([], |row| {
+                Ok(BackupEvent {
+                    todo_id: row.get(0)?,
+                    list_id: row.get(1)?,
+                    kind: row.get(2)?,
+                    value: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let views = tx
+            .prepare("SELECT name, query, layout, position FROM saved_views ORDER BY position, id")?
+            .query_map([], |row| {
+                Ok(BackupView { name: row.get(0)?, query: row.get(1)?, layout: row.get(2)?, position: row.get(3)? })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let reset_schedules = tx
+            .prepare("SELECT list_id, todo_id, weekday, time, last_reset_at FROM reset_schedules ORDER BY id")?
+            .query_map([], |row| {
+                Ok(BackupSchedule {
+                    list_id: row.get(0)?,
+                    todo_id: row.get(1)?,
+                    frequency: ResetFrequency::from_weekday(row.get(2)?),
+                    time: row.get(3)?,
+                    last_reset_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let notes = tx
+            .prepare(
+                "SELECT n.id, n.title, COALESCE(n.content, ''), n.position, n.updated_at,
+                        w.note_id IS NOT NULL, w.width, w.height, w.x, w.y, w.pinned
+                 FROM notes n LEFT JOIN note_windows w ON w.note_id = n.id
+                 ORDER BY n.position, n.id",
+            )?
+            .query_map([], |row| {
+                let window = if row.get(5)? {
+                    Some(WindowState {
+                        width: row.get(6)?,
+                        height: row.get(7)?,
+                        x: row.get(8)?,
+                        y: row.get(9)?,
+                        pinned: row.get::<_, i32>(10)? != 0,
+                    })
+                } else {
+                    None
+                };
+                Ok(BackupNote {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    content: row.get(2)?,
+                    position: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    window,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let settings = tx
+            .prepare("SELECT key, value FROM settings")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(Backup {
+            format: backup::FORMAT.to_string(),
+            version: backup::VERSION,
+            exported_at: now_millis(),
+            lists,
+            todos,
+            events,
+            views,
+            reset_schedules,
+            notes,
+            window: window_state(&tx)?,
+            settings,
+        })
+    }
+
+    /// Loads a backup in one transaction. Replacing also clears the undo
+    /// history, whose entries describe rows that no longer exist; merging
+    /// is not undoable either, as notes and views are not journaled.
+    pub fn restore_backup(&self, backup: &Backup, mode: RestoreMode) -> Result<RestoreSummary> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let replace = mode == RestoreMode::Replace;
+        // Replacing keeps the backup's ids, merging lets SQLite pick new ones
+        let keep = |id: u32| replace.then_some(id);
+
+        if replace {
+            for table in [
+                "todo_tags", "reset_schedules", "todos", "tags", "lists", "saved_views", "notes",
+                "window_state", "settings", "history", "todo_events",
+            ] {
+                tx.execute(&format!("DELETE FROM {}", table), [])?;
+            }
+        }
+
+        // Appended after what is already there when merging
+        let offset = |table: &str| -> Result<i32> {
+            if replace {
+                return Ok(0);
+            }
+            Ok(tx.query_row(&format!("SELECT COALESCE(MAX(position), -1) + 1 FROM {}", table), [], |row| row.get(0))?)
+        };
+        let mut summary = RestoreSummary::default();
+
+        let list_offset = offset("lists")?;
+        let mut list_ids = HashMap::new();
+        for list in &backup.lists {
+            tx.execute(
+                "INSERT INTO lists (id, name, position) VALUES (?1, ?2, ?3)",
+                params![keep(list.id), list.name, list_offset + list.position],
+            )?;
+            list_ids.insert(list.id, tx.last_insert_rowid() as u32);
+            summary.lists += 1;
+        }
+
+        let mut todo_ids = HashMap::new();
+        for todo in backup.todos_parents_first()? {
+            tx.execute(
+                "INSERT INTO todos (id, list_id, parent_id, position, text, description, completed, completed_at,
+                                    target_count, current_count, due_at, scheduled_for, priority, recurrence,
+                                    deleted_at, archived_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                params![
+                    keep(todo.id),
+                    list_ids[&todo.list_id],
+                    todo.parent_id.map(|p| todo_ids[&p]),
+                    todo.position,
+                    todo.text,
+                    todo.description,
+                    todo.completed,
+                    todo.completed_at,
+                    todo.target_count,
+                    todo.current_count,
+                    todo.due_at,
+                    todo.scheduled_for,
+                    todo.priority,
+                    todo.recurrence.as_ref().map(RecurrenceRule::to_json),
+                    todo.deleted_at,
+                    todo.archived_at,
+                ],
+            )?;
+            let id = tx.last_insert_rowid() as u32;
+            sync_tags(&tx, id, &todo.text)?;
+            todo_ids.insert(todo.id, id);
+            summary.todos += 1;
+        }
+
+        for event in &backup.events {
+            // When replacing, events about purged items keep their old ids
+            let todo_id = match event.todo_id {
+                Some(id) if !replace => match todo_ids.get(&id) {
+                    Some(new_id) => Some(*new_id),
+                    None => continue,
+                },
+                todo_id => todo_id,
+            };
+            let list_id = match list_ids.get(&event.list_id) {
+                Some(new_id) => *new_id,
+                None if replace => event.list_id,
+                None => continue,
+            };
+            // history_id 0 ties the event to no undo entry, so it is never
+            // hidden or picked up by the next journaled change
+            tx.execute(
+                "INSERT INTO todo_events (todo_id, list_id, kind, value, created_at, history_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+                params![todo_id, list_id, event.kind.as_str(), event.value, event.created_at],
+            )?;
+        }
+
+        let view_offset = offset("saved_views")?;
+        for view in &backup.views {
+            let mut name = view.name.clone();
+            let mut copy = 2;
+            while tx
+                .query_row("SELECT 1 FROM saved_views WHERE name = ?1", params![name], |_| Ok(()))
+                .optional()?
+                .is_some()
+            {
+                name = format!("{} ({})", view.name, copy);
+                copy += 1;
+            }
+            tx.execute(
+                "INSERT INTO saved_views (name, query, layout, position) VALUES (?1, ?2, ?3, ?4)",
+                params![name, view.query, view.layout.as_str(), view_offset + view.position],
+            )?;
+            summary.views += 1;
+        }
+
+        for schedule in &backup.reset_schedules {
+            tx.execute(
+                "INSERT INTO reset_schedules (list_id, todo_id, weekday, time, last_reset_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    list_ids[&schedule.list_id],
+                    schedule.todo_id.map(|t| todo_ids[&t]),
+                    schedule.frequency.weekday(),
+                    schedule.time,
+                    schedule.last_reset_at,
+                ],
+            )?;
+        }
+
+        let note_offset = offset("notes")?;
+        for note in &backup.notes {
+            tx.execute(
+                "INSERT INTO notes (id, title, content, position, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![keep(note.id), note.title, note.content, note_offset + note.position, note.updated_at],
+            )?;
+            let id = tx.last_insert_rowid() as u32;
+            if let Some(window) = note.window.as_ref().filter(|_| replace) {
+                tx.execute(
+                    "INSERT INTO note_windows (note_id, width, height, x, y, pinned) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![id, window.width, window.height, window.x, window.y, window.pinned],
+                )?;
+            }
+            summary.notes += 1;
+        }
+
+        if replace {
+            if let Some(window) = &backup.window {
+                tx.execute(
+                    "INSERT INTO window_state (id, width, height, x, y, pinned) VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+                    params![window.width, window.height, window.x, window.y, window.pinned],
+                )?;
+            }
+            for (key, value) in &backup.settings {
+                set_setting(&tx, key, value)?;
+            }
+        } else {
+            compact_list_positions(&tx)?;
+            compact_note_positions(&tx)?;
+        }
+
+        tx.commit()?;
+        println!("[DB] Restored backup ({:?}): {:?}", mode, summary);
+        Ok(summary)
+    }
+}
+
+/// The label of an applied undo or redo step. A dropped one is reported as
+/// an error once the transaction that dropped it has committed.
+fn step_result(action: &str, step: history::Step) -> Result<Option<String>> {
+    match step {
+        history::Step::Nothing => Ok(None),
+        history::Step::Applied(_, label) => Ok(Some(label)),
+        history::Step::Dropped(label) => Err(AppError::ConstraintViolation(format!(
+            "Cannot {} \"{}\", the items it changed are gone",
+            action, label
+        ))),
+    }
+}
+
+fn window_state(conn: &Connection) -> Result<Option<WindowState>> {
+    let state = conn
+        .query_row("SELECT width, height, x, y, pinned FROM window_state WHERE id = 1", [], |row| {
+            Ok(WindowState {
+                width: row.get(0)?,
+                height: row.get(1)?,
+                x: row.get(2)?,
+                y: row.get(3)?,
+                pinned: row.get::<_, i32>(4)? != 0,
+            })
+        })
+        .optional()?;
+    Ok(state)
+}
+
+/// Moves todos whose parent no longer exists to the end of the root list.
+/// Returns the number of items that were re-attached.
+fn repair_orphans(conn: &mut Connection) -> Result<usize> {
+    let tx = conn.transaction()?;
+
+    let repaired = tx.execute(
+        "WITH orphans AS (
+            SELECT id, list_id,
+                   ROW_NUMBER() OVER (PARTITION BY list_id ORDER BY parent_id, position, id) - 1 AS rn
+            FROM todos
+            WHERE parent_id IS NOT NULL
+              AND parent_id NOT IN (SELECT id FROM todos)
+        ),
+        root_ends AS (
+            SELECT list_id, MAX(position) + 1 AS root_end
+            FROM todos WHERE parent_id IS NULL GROUP BY list_id
+        )
+        UPDATE todos SET parent_id = NULL, position = COALESCE(root_ends.root_end, 0) + orphans.rn
+        FROM orphans LEFT JOIN root_ends ON root_ends.list_id = orphans.list_id
+        WHERE todos.id = orphans.id",
+        [],
+    )?;
+
+    if repaired > 0 {
+        let list_ids = tx
+            .prepare("SELECT DISTINCT list_id FROM todos WHERE parent_id IS NULL")?
+            .query_map([], |row| row.get::<_, u32>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for list_id in list_ids {
+            compact_positions(&tx, list_id, None)?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(repaired)
+}
+
+fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Validates a `YYYY-MM-DD` date and returns it normalized.
+fn parse_date(value: &str) -> Result<String> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT)
+        .map(|d| d.format(DATE_FORMAT).to_string())
+        .map_err(|_| AppError::InvalidInput(format!("Invalid date \"{}\", expected YYYY-MM-DD", value)))
+}
+
+fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()?)
+}
+
+fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+fn trash_retention_days(conn: &Connection) -> Result<u32> {
+    Ok(get_setting(conn, "trash_retention_days")?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS))
+}
+
+fn sort_mode(conn: &Connection) -> Result<SortMode> {
+    Ok(match get_setting(conn, "sort_mode")?.as_deref() {
+        Some("priority") => SortMode::Priority,
+        _ => SortMode::Manual,
+    })
+}
+
+/// Hard-deletes the rows selected by `roots` together with all their
+/// descendants. Explicit because legacy tables have no cascading foreign key.
+fn purge(conn: &Connection, roots: &str, params: impl rusqlite::Params) -> Result<usize> {
+    let purged = conn.execute(
+        &format!(
+            "WITH RECURSIVE doomed(id) AS (
+                {}
+                UNION
+                SELECT t.id FROM todos t
+                JOIN doomed d ON t.parent_id = d.id
+            )
+            DELETE FROM todos WHERE id IN doomed",
+            roots
+        ),
+        params,
+    )?;
+    prune_tags(conn)?;
+    Ok(purged)
+}
+
+/// Makes the item's rows in `todo_tags` match the `#tags` in its text.
+fn sync_tags(conn: &Connection, id: u32, text: &str) -> Result<()> {
+    conn.execute("DELETE FROM todo_tags WHERE todo_id = ?1", params![id])?;
+    for name in markers::extract_tags(text) {
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![name])?;
+        conn.execute(
+            "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+            params![id, name],
+        )?;
+    }
+    prune_tags(conn)
 }
 
-pub fn init_db(app_handle: &AppHandle) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    std::fs::create_dir_all(&app_dir).unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    println!("Database path: {:?}", db_path);
-    
-    let conn = Connection::open(db_path)?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS notes (
-            id INTEGER PRIMARY KEY,
-            content TEXT
-        )",
-        [],
+/// Drops tags no item refers to any more. Trashed items keep theirs.
+fn prune_tags(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM todo_tags)", [])?;
+    Ok(())
+}
+
+/// Tag names of every item, keyed by todo id.
+fn todo_tags(conn: &Connection) -> Result<HashMap<u32, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT tt.todo_id, g.name FROM todo_tags tt
+         JOIN tags g ON g.id = tt.tag_id
+         ORDER BY g.name",
     )?;
+    let mut tags: HashMap<u32, Vec<String>> = HashMap::new();
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, name) = row?;
+        tags.entry(id).or_default().push(name);
+    }
+    Ok(tags)
+}
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS todos (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            text TEXT NOT NULL,
-            completed BOOLEAN NOT NULL,
-            parent_id INTEGER,
-            position INTEGER DEFAULT 0,
-            target_count INTEGER,
-            current_count INTEGER DEFAULT 0,
-            FOREIGN KEY(parent_id) REFERENCES todos(id) ON DELETE CASCADE
-        )",
-        [],
+/// Takes an item out of the trash (`column` = `deleted_at`) or the archive
+/// (`archived_at`) and puts it back at its old parent and position, or at
+/// the end of the root list if that parent is no longer live. `place` names
+/// where it came from in errors.
+fn restore_subtree(tx: &Transaction, id: u32, column: &str, place: &str) -> Result<()> {
+    let (list_id, parent_id, position, hidden_at): (u32, Option<u32>, i32, i64) = tx
+        .query_row(
+            &format!(
+                "SELECT list_id, parent_id, position, {column} FROM todos
+                 WHERE id = ?1 AND {column} IS NOT NULL"
+            ),
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Todo {} in {}", id, place)))?;
+
+    let parent_alive = match parent_id {
+        Some(pid) => tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM todos WHERE id = ?1 AND deleted_at IS NULL AND archived_at IS NULL)",
+            params![pid],
+            |row| row.get(0),
+        )?,
+        None => true,
+    };
+    let parent_id = if parent_alive { parent_id } else { None };
+
+    let sibling_count: i32 = tx.query_row(
+        "SELECT COUNT(*) FROM todos WHERE list_id = ?1 AND parent_id IS ?2 AND deleted_at IS NULL AND archived_at IS NULL",
+        params![list_id, parent_id],
+        |row| row.get(0),
     )?;
+    let position = if parent_alive { position.min(sibling_count) } else { sibling_count };
 
-    // Migration: Add columns if they don't exist (simplistic approach)
-    let _ = conn.execute("ALTER TABLE todos ADD COLUMN parent_id INTEGER", []);
-    let _ = conn.execute("ALTER TABLE todos ADD COLUMN position INTEGER DEFAULT 0", []);
-    let _ = conn.execute("ALTER TABLE todos ADD COLUMN target_count INTEGER", []);
-    let _ = conn.execute("ALTER TABLE todos ADD COLUMN current_count INTEGER DEFAULT 0", []);
+    tx.execute(
+        "UPDATE todos SET position = position + 1
+         WHERE list_id = ?3 AND parent_id IS ?1 AND position >= ?2 AND deleted_at IS NULL AND archived_at IS NULL",
+        params![parent_id, position, list_id],
+    )?;
+    tx.execute(
+        &format!("UPDATE todos SET parent_id = ?1, position = ?2, {column} = NULL WHERE id = ?3"),
+        params![parent_id, position, id],
+    )?;
 
-    // Create window_state table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS window_state (
-            id INTEGER PRIMARY KEY CHECK (id = 1),
-            width REAL NOT NULL DEFAULT 300,
-            height REAL NOT NULL DEFAULT 300,
-            x REAL NOT NULL DEFAULT 100,
-            y REAL NOT NULL DEFAULT 100,
-            pinned INTEGER NOT NULL DEFAULT 0
-        )",
-        [],
+    // Descendants hidden in the same batch come back with it
+    let restored = tx.execute(
+        &format!(
+            "WITH RECURSIVE batch(id) AS (
+                SELECT id FROM todos WHERE parent_id = ?1 AND {column} = ?2
+                UNION ALL
+                SELECT t.id FROM todos t
+                JOIN batch b ON t.parent_id = b.id
+                WHERE t.{column} = ?2
+            )
+            UPDATE todos SET {column} = NULL WHERE id IN batch"
+        ),
+        params![id, hidden_at],
     )?;
+    println!("[DB] restore from {}: restored {} with {} descendants", place, id, restored);
 
-    // Initialize default note if empty
-    let count: i32 = conn.query_row("SELECT count(*) FROM notes", [], |row| row.get(0))?;
-    if count == 0 {
-        conn.execute("INSERT INTO notes (id, content) VALUES (1, '')", [])?;
-    }
+    update_ancestors(tx, id)?;
 
     Ok(())
 }
 
-pub fn get_note(app_handle: &AppHandle) -> Result<String> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    let content: String = conn.query_row(
-        "SELECT content FROM notes WHERE id = 1",
-        [],
-        |row| row.get(0),
+/// Hides a live item and its live subtree from the list at `archived_at`.
+fn archive_subtree(tx: &Transaction, id: u32, archived_at: i64) -> Result<usize> {
+    let (list_id, parent_id, _) = todo_location(tx, id)?;
+    let archived = tx.execute(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT ?1
+            UNION ALL
+            SELECT t.id FROM todos t
+            JOIN subtree s ON t.parent_id = s.id
+            WHERE t.deleted_at IS NULL AND t.archived_at IS NULL
+        )
+        UPDATE todos SET archived_at = ?2 WHERE id IN subtree",
+        params![id, archived_at],
     )?;
-    
-    Ok(content)
+    compact_positions(tx, list_id, parent_id)?;
+    update_ancestors(tx, id)?;
+    Ok(archived)
 }
 
-pub fn save_note(app_handle: &AppHandle, content: String) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    conn.execute(
-        "UPDATE notes SET content = ?1 WHERE id = 1",
-        params![content],
+/// Texts of the ancestors of a todo, root first.
+fn ancestor_texts(conn: &Connection, id: u32) -> Result<Vec<String>> {
+    let texts = conn
+        .prepare(
+            "WITH RECURSIVE ancestors(id, parent_id, text, depth) AS (
+                SELECT p.id, p.parent_id, p.text, 0 FROM todos c JOIN todos p ON p.id = c.parent_id
+                WHERE c.id = ?1
+                UNION ALL
+                SELECT t.id, t.parent_id, t.text, a.depth + 1 FROM todos t
+                JOIN ancestors a ON t.id = a.parent_id
+            )
+            SELECT text FROM ancestors ORDER BY depth DESC",
+        )?
+        .query_map(params![id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(texts)
+}
+
+/// Returns `(list_id, parent_id, position)` of a live todo.
+fn todo_location(conn: &Connection, id: u32) -> Result<(u32, Option<u32>, i32)> {
+    conn.query_row(
+        "SELECT list_id, parent_id, position FROM todos WHERE id = ? AND deleted_at IS NULL AND archived_at IS NULL",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found(format!("Todo {}", id)))
+}
+
+fn expect_list(conn: &Connection, id: u32) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM lists WHERE id = ?1)",
+        params![id],
+        |row| row.get(0),
     )?;
-    
+    if !exists {
+        return Err(AppError::not_found(format!("List {}", id)));
+    }
     Ok(())
 }
 
-pub fn get_todos(app_handle: &AppHandle) -> Result<Vec<TodoItem>> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    let mut stmt = conn.prepare("SELECT id, text, completed, parent_id, position, target_count, current_count FROM todos ORDER BY position ASC")?;
-    let todo_iter = stmt.query_map([], |row| {
-        Ok(TodoItem {
-            id: row.get(0)?,
-            text: row.get(1)?,
-            completed: row.get(2)?,
-            parent_id: row.get(3)?,
-            position: row.get(4)?,
-            target_count: row.get(5)?,
-            current_count: row.get(6)?,
-        })
-    })?;
+/// Copies a note's current text into `note_revisions` unless it is empty,
+/// unchanged since the newest revision, or (without `force`) that revision
+/// is recent. Then drops revisions past the count and age limits.
+fn snapshot_note(tx: &Transaction, id: u32, now: i64, force: bool) -> Result<()> {
+    let content: String = tx
+        .query_row("SELECT COALESCE(content, '') FROM notes WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("Note {}", id)))?;
+    if content.is_empty() {
+        return Ok(());
+    }
 
-    let mut todos = Vec::new();
-    for todo in todo_iter {
-        todos.push(todo?);
+    let newest: Option<(String, i64)> = tx
+        .query_row(
+            "SELECT content, created_at FROM note_revisions WHERE note_id = ?1
+             ORDER BY created_at DESC, id DESC LIMIT 1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    match newest {
+        Some((newest, _)) if newest == content => return Ok(()),
+        Some((_, created_at)) if !force && now - created_at < revisions::SNAPSHOT_INTERVAL_MS => return Ok(()),
+        _ => {}
     }
-    
-    Ok(todos)
+
+    tx.execute(
+        "INSERT INTO note_revisions (note_id, content, created_at) VALUES (?1, ?2, ?3)",
+        params![id, content, now],
+    )?;
+    tx.execute(
+        "DELETE FROM note_revisions
+         WHERE created_at < ?1
+            OR (note_id = ?2 AND id NOT IN (
+                SELECT id FROM note_revisions WHERE note_id = ?2
+                ORDER BY created_at DESC, id DESC LIMIT ?3
+            ))",
+        params![now - revisions::MAX_AGE_DAYS * 24 * 60 * 60 * 1000, id, revisions::MAX_REVISIONS],
+    )?;
+    Ok(())
 }
 
-pub fn save_todo(app_handle: &AppHandle, text: String) -> Result<u32> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    // Get max position to append to end
-    let max_pos: Result<i32> = conn.query_row(
-        "SELECT COALESCE(MAX(position), -1) FROM todos WHERE parent_id IS NULL",
-        [],
+fn expect_note(conn: &Connection, id: u32) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM notes WHERE id = ?1)",
+        params![id],
         |row| row.get(0),
-    );
-    let position = max_pos.unwrap_or(-1) + 1;
-    
-    println!("[DB] Creating new todo with position: {}", position);
+    )?;
+    if !exists {
+        return Err(AppError::not_found(format!("Note {}", id)));
+    }
+    Ok(())
+}
+
+fn list_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("List name cannot be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+fn note_title(title: &str) -> Result<String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(AppError::InvalidInput("Note title cannot be empty".to_string()));
+    }
+    Ok(title.to_string())
+}
+
+fn view_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("View name cannot be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+// Turns "0 rows affected" into a NotFound error for the UI.
+fn expect_todo(affected: usize, id: u32) -> Result<()> {
+    if affected == 0 {
+        return Err(AppError::not_found(format!("Todo {}", id)));
+    }
+    Ok(())
+}
 
+/// Renumbers the live children of `parent_id` in `list_id` to 0..n, keeping
+/// their current order. Trashed items keep the position they had so they can
+/// be restored.
+fn compact_positions(conn: &Connection, list_id: u32, parent_id: Option<u32>) -> Result<()> {
     conn.execute(
-        "INSERT INTO todos (text, completed, parent_id, position) VALUES (?1, ?2, ?3, ?4)",
-        params![text, false, None::<u32>, position],
+        "WITH ordered AS (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY position, id) - 1 AS new_pos
+            FROM todos WHERE list_id = ?1 AND parent_id IS ?2 AND deleted_at IS NULL AND archived_at IS NULL
+        )
+        UPDATE todos SET position = ordered.new_pos
+        FROM ordered WHERE todos.id = ordered.id AND todos.position != ordered.new_pos",
+        params![list_id, parent_id],
     )?;
-    
-    let id = conn.last_insert_rowid() as u32;
-    println!("[DB] Created todo id={} at position={}", id, position);
-    Ok(id)
+    Ok(())
 }
 
-pub fn update_todo(app_handle: &AppHandle, id: u32, completed: bool) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let mut conn = Connection::open(db_path)?;
-    
-    let tx = conn.transaction()?;
+fn compact_note_positions(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "WITH ordered AS (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY position, id) - 1 AS new_pos FROM notes
+        )
+        UPDATE notes SET position = ordered.new_pos
+        FROM ordered WHERE notes.id = ordered.id AND notes.position != ordered.new_pos",
+        [],
+    )?;
+    Ok(())
+}
 
-    println!("[DB] update_todo: id={}, completed={}", id, completed);
+fn compact_list_positions(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "WITH ordered AS (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY position, id) - 1 AS new_pos FROM lists
+        )
+        UPDATE lists SET position = ordered.new_pos
+        FROM ordered WHERE lists.id = ordered.id AND lists.position != ordered.new_pos",
+        [],
+    )?;
+    Ok(())
+}
 
+/// Sets `completed` on an item and its descendants, then re-derives the
+/// status of every ancestor from its children.
+/// Sets an item and its subtree to `completed` and returns the ancestors
+/// that became complete with it, nearest first.
+fn set_completed(tx: &Transaction, id: u32, completed: bool) -> Result<Vec<u32>> {
     // 1. Update the target item
-    tx.execute(
+    let updated = tx.execute(
         "UPDATE todos SET completed = ?1 WHERE id = ?2",
         params![completed, id],
     )?;
+    expect_todo(updated, id)?;
 
     // 2. Cascade Down: Update all descendants
     // Use recursive CTE to find all descendant IDs
     let affected = tx.execute(
         "WITH RECURSIVE descendants(id) AS (
-            SELECT id FROM todos WHERE parent_id = ?1
+            SELECT id FROM todos WHERE parent_id = ?1 AND deleted_at IS NULL AND archived_at IS NULL
             UNION ALL
             SELECT t.id FROM todos t
             JOIN descendants d ON t.parent_id = d.id
+            WHERE t.deleted_at IS NULL AND t.archived_at IS NULL
         )
         UPDATE todos SET completed = ?2 WHERE id IN descendants",
         params![id, completed],
@@ -189,6 +2626,228 @@ pub fn update_todo(app_handle: &AppHandle, id: u32, completed: bool) -> Result<(
     println!("[DB] Cascade Down: Updated {} descendants", affected);
 
     // 3. Cascade Up: Update ancestors
+    update_ancestors(tx, id)
+}
+
+/// Completes an item, logs it and applies its recurrence rule, then does the
+/// same for every ancestor the completion finished.
+fn complete_and_recur(tx: &Transaction, id: u32) -> Result<()> {
+    let finished = set_completed(tx, id, true)?;
+    log_event(tx, id, EventKind::Completed, None)?;
+    recur(tx, id)?;
+    for ancestor in finished {
+        // Still done, unless rescheduling an item below reopened it
+        let completed: bool =
+            tx.query_row("SELECT completed FROM todos WHERE id = ?1", params![ancestor], |row| row.get(0))?;
+        if completed {
+            recur(tx, ancestor)?;
+        }
+    }
+    Ok(())
+}
+
+/// Applies the recurrence rule of an item that was just completed, if it
+/// has one.
+fn recur(tx: &Transaction, id: u32) -> Result<()> {
+    let (rule, due_at, scheduled_for): (Option<String>, Option<String>, Option<String>) = tx.query_row(
+        "SELECT recurrence, due_at, scheduled_for FROM todos WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let Some(rule) = rule.as_deref().and_then(RecurrenceRule::from_json) else {
+        return Ok(());
+    };
+
+    let today = chrono::Local::now().date_naive();
+    let (due_at, scheduled_for) = next_dates(&rule.repeat, due_at, scheduled_for, today)?;
+
+    let target = match rule.mode {
+        RecurrenceMode::Reschedule => {
+            tx.execute(
+                "WITH RECURSIVE subtree(id) AS (
+                    SELECT ?1
+                    UNION ALL
+                    SELECT t.id FROM todos t
+                    JOIN subtree s ON t.parent_id = s.id
+                    WHERE t.deleted_at IS NULL AND t.archived_at IS NULL
+                )
+                UPDATE todos SET completed = 0, current_count = COALESCE(target_count, 0)
+                WHERE id IN subtree",
+                params![id],
+            )?;
+            id
+        }
+        RecurrenceMode::Spawn => {
+            let (list_id, parent_id, position) = todo_location(tx, id)?;
+            tx.execute(
+                "UPDATE todos SET position = position + 1
+                 WHERE list_id = ?1 AND parent_id IS ?2 AND position > ?3 AND deleted_at IS NULL AND archived_at IS NULL",
+                params![list_id, parent_id, position],
+            )?;
+            let copy = copy_subtree(tx, id, parent_id, position + 1)?;
+            // Only the newest occurrence repeats
+            tx.execute("UPDATE todos SET recurrence = NULL WHERE id = ?1", params![id])?;
+            copy
+        }
+    };
+
+    tx.execute(
+        "UPDATE todos SET due_at = ?1, scheduled_for = ?2 WHERE id = ?3",
+        params![due_at, scheduled_for, target],
+    )?;
+    println!("[DB] recur: id={} next due={:?} scheduled={:?} as {}", id, due_at, scheduled_for, target);
+
+    update_ancestors(tx, target)?;
+    Ok(())
+}
+
+/// Moves the dates of a recurring item to its next occurrence. The due date
+/// drives the schedule and the scheduled date keeps its distance to it. An
+/// item without dates gets a due date.
+fn next_dates(
+    repeat: &recurrence::Repeat,
+    due_at: Option<String>,
+    scheduled_for: Option<String>,
+    today: NaiveDate,
+) -> Result<(Option<String>, Option<String>)> {
+    let parse = |d: Option<String>| d.and_then(|d| NaiveDate::parse_from_str(&d, DATE_FORMAT).ok());
+    let format = |d: NaiveDate| Some(d.format(DATE_FORMAT).to_string());
+    let (due, scheduled) = (parse(due_at), parse(scheduled_for));
+
+    Ok(match (due, scheduled) {
+        (Some(due), scheduled) => {
+            let next_due = recurrence::next_date(repeat, Some(due), today)?;
+            let next_scheduled = match scheduled {
+                Some(s) => Some(s.checked_add_signed(next_due - due).ok_or_else(|| {
+                    AppError::InvalidInput(format!("Scheduled date {} moves out of range", s))
+                })?),
+                None => None,
+            };
+            (format(next_due), next_scheduled.and_then(format))
+        }
+        (None, Some(scheduled)) => (None, format(recurrence::next_date(repeat, Some(scheduled), today)?)),
+        (None, None) => (format(recurrence::next_date(repeat, None, today)?), None),
+    })
+}
+
+/// Inserts an unchecked copy of `source` and its live descendants under
+/// `parent_id` at `position`, with countdowns reset. Returns the copy's id.
+fn copy_subtree(tx: &Transaction, source: u32, parent_id: Option<u32>, position: i32) -> Result<u32> {
+    tx.execute(
+        "INSERT INTO todos (list_id, text, completed, parent_id, position, target_count, current_count,
+                            due_at, scheduled_for, priority, recurrence, description)
+         SELECT list_id, text, 0, ?2, ?3, target_count, COALESCE(target_count, 0),
+                due_at, scheduled_for, priority, recurrence, description
+         FROM todos WHERE id = ?1",
+        params![source, parent_id, position],
+    )?;
+    let copy = tx.last_insert_rowid() as u32;
+
+    let text: String = tx.query_row("SELECT text FROM todos WHERE id = ?1", params![copy], |row| row.get(0))?;
+    sync_tags(tx, copy, &text)?;
+
+    let children = tx
+        .prepare("SELECT id, position FROM todos WHERE parent_id = ?1 AND deleted_at IS NULL AND archived_at IS NULL ORDER BY position")?
+        .query_map(params![source], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, i32>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (child, child_position) in children {
+        copy_subtree(tx, child, Some(copy), child_position)?;
+    }
+
+    Ok(copy)
+}
+
+/// Unchecks and refills the countdowns of a whole list, or of the live
+/// subtree under `todo_id`.
+fn reset_todos(tx: &Transaction, list_id: u32, todo_id: Option<u32>) -> Result<()> {
+    match todo_id {
+        None => {
+            tx.execute(
+                "UPDATE todos SET completed = 0, current_count = COALESCE(target_count, 0)
+                 WHERE list_id = ?1 AND deleted_at IS NULL AND archived_at IS NULL",
+                params![list_id],
+            )?;
+            tx.execute(
+                "INSERT INTO todo_events (todo_id, list_id, kind, created_at) VALUES (NULL, ?1, ?2, ?3)",
+                params![list_id, EventKind::Reset.as_str(), now_millis()],
+            )?;
+        }
+        Some(id) => {
+            tx.execute(
+                "WITH RECURSIVE subtree(id) AS (
+                    SELECT id FROM todos WHERE id = ?1 AND deleted_at IS NULL AND archived_at IS NULL
+                    UNION ALL
+                    SELECT t.id FROM todos t
+                    JOIN subtree s ON t.parent_id = s.id
+                    WHERE t.deleted_at IS NULL AND t.archived_at IS NULL
+                )
+                UPDATE todos SET completed = 0, current_count = COALESCE(target_count, 0)
+                WHERE id IN subtree",
+                params![id],
+            )?;
+            log_event(tx, id, EventKind::Reset, None)?;
+            update_ancestors(tx, id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Inserts an imported checklist item and its sub-items, returning the
+/// item's id. Checked items count as completed now, since the
+/// `todos_completed_at` trigger only sees updates.
+fn insert_outline(
+    tx: &Transaction,
+    list_id: u32,
+    parent_id: Option<u32>,
+    position: i32,
+    item: &checklist::OutlineItem,
+) -> Result<u32> {
+    let (text, priority) = markers::extract_priority(&item.text);
+    let (target, remaining) = match item.count {
+        Some((target, remaining)) => (Some(target), remaining),
+        None => (None, 0),
+    };
+
+    tx.execute(
+        "INSERT INTO todos (list_id, text, completed, completed_at, parent_id, position, priority, target_count, current_count)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            list_id,
+            text,
+            item.completed,
+            item.completed.then(now_millis),
+            parent_id,
+            position,
+            priority.unwrap_or_default(),
+            target,
+            remaining,
+        ],
+    )?;
+    let id = tx.last_insert_rowid() as u32;
+    sync_tags(tx, id, &text)?;
+
+    for (position, child) in item.children.iter().enumerate() {
+        insert_outline(tx, list_id, Some(id), position as i32, child)?;
+    }
+    Ok(id)
+}
+
+/// Appends an event about one item to `todo_events`. Call it inside
+/// `TodoStore::journaled`, which ties it to the undo entry, or
+/// `TodoStore::unjournaled`, which ties it to none.
+fn log_event(conn: &Connection, id: u32, kind: EventKind, value: Option<i64>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO todo_events (todo_id, list_id, kind, value, created_at)
+         SELECT id, list_id, ?2, ?3, ?4 FROM todos WHERE id = ?1",
+        params![id, kind.as_str(), value, now_millis()],
+    )?;
+    Ok(())
+}
+
+/// Re-derives the completion of every ancestor of `id` from its live children.
+/// Returns the ancestors this completed, nearest first.
+fn update_ancestors(tx: &Transaction, id: u32) -> Result<Vec<u32>> {
+    let mut completed = Vec::new();
     let mut current_id = id;
     loop {
         // Get parent of current_id
@@ -209,205 +2868,439 @@ pub fn update_todo(app_handle: &AppHandle, id: u32, completed: bool) -> Result<(
         // Check siblings status
         let (total, completed_count): (i32, i32) = tx.query_row(
             "SELECT COUNT(*), COALESCE(SUM(CASE WHEN completed THEN 1 ELSE 0 END), 0)
-             FROM todos WHERE parent_id = ?",
+             FROM todos WHERE parent_id = ? AND deleted_at IS NULL AND archived_at IS NULL",
             params![parent_id],
             |row| Ok((row.get(0)?, row.get(1)?)),
         )?;
 
+        // A parent whose last child went to the trash keeps its own status
+        if total == 0 {
+            break;
+        }
+
         let new_parent_status = total > 0 && total == completed_count;
         println!("[DB] Cascade Up: Checking parent={}, total={}, completed={}, new_status={}", parent_id, total, completed_count, new_parent_status);
 
         // Update parent
-        tx.execute(
-            "UPDATE todos SET completed = ? WHERE id = ?",
+        let changed = tx.execute(
+            "UPDATE todos SET completed = ?1 WHERE id = ?2 AND completed IS NOT ?1",
             params![new_parent_status, parent_id],
         )?;
+        if changed > 0 && new_parent_status {
+            completed.push(parent_id);
+        }
 
         // Move up
         current_id = parent_id;
     }
-    
-    tx.commit()?;
-    println!("[DB] update_todo transaction committed");
-    
-    Ok(())
-}
 
-pub fn update_todo_text(app_handle: &AppHandle, id: u32, text: String) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    conn.execute(
-        "UPDATE todos SET text = ?1 WHERE id = ?2",
-        params![text, id],
-    )?;
-    
-    Ok(())
+    Ok(completed)
 }
 
-pub fn delete_todo(app_handle: &AppHandle, id: u32) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    conn.execute(
-        "DELETE FROM todos WHERE id = ?1",
-        params![id],
-    )?;
-    
-    Ok(())
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-pub fn move_todo(app_handle: &AppHandle, id: u32, target_parent_id: Option<u32>, target_position: i32) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let mut conn = Connection::open(db_path)?;
-    
-    let tx = conn.transaction()?;
+    fn store() -> TodoStore {
+        TodoStore::open_in_memory().unwrap()
+    }
 
-    // 1. Get current state
-    let (current_parent_id, current_position): (Option<u32>, i32) = tx.query_row(
-        "SELECT parent_id, position FROM todos WHERE id = ?",
-        params![id],
-        |row| Ok((row.get(0)?, row.get(1)?)),
-    )?;
+    fn todo(store: &TodoStore, id: u32) -> TodoItem {
+        let list_id = todo_location(&store.conn(), id).unwrap().0;
+        store.get_todos(list_id).unwrap().into_iter().find(|t| t.id == id).unwrap()
+    }
 
-    // 2. Remove from old list (shift items up)
-    if let Some(pid) = current_parent_id {
-        tx.execute(
-            "UPDATE todos SET position = position - 1 WHERE parent_id = ? AND position > ?",
-            params![pid, current_position],
-        )?;
-    } else {
-        tx.execute(
-            "UPDATE todos SET position = position - 1 WHERE parent_id IS NULL AND position > ?",
-            params![current_position],
-        )?;
+    /// `(id, parent_id, position)` of the live items of a list, ordered by id.
+    fn tree(store: &TodoStore, list_id: u32) -> Vec<(u32, Option<u32>, i32)> {
+        let todos = store.get_todos(list_id).unwrap();
+        let mut tree: Vec<_> = todos.into_iter().map(|t| (t.id, t.parent_id, t.position)).collect();
+        tree.sort();
+        tree
     }
 
-    // 3. Make space in new list (shift items down)
-    if let Some(pid) = target_parent_id {
-        tx.execute(
-            "UPDATE todos SET position = position + 1 WHERE parent_id = ? AND position >= ?",
-            params![pid, target_position],
-        )?;
-    } else {
-        tx.execute(
-            "UPDATE todos SET position = position + 1 WHERE parent_id IS NULL AND position >= ?",
-            params![target_position],
-        )?;
+    fn completions(store: &TodoStore, id: u32) -> usize {
+        let events = store.get_todo_events(Some(id), None, None).unwrap();
+        events.iter().filter(|e| e.kind == EventKind::Completed).count()
     }
 
-    // 4. Update the item itself
-    tx.execute(
-        "UPDATE todos SET parent_id = ?, position = ? WHERE id = ?",
-        params![target_parent_id, target_position, id],
-    )?;
+    #[test]
+    fn decrement_completes_once() {
+        let store = store();
+        let id = store.save_todo(1, "Push-ups".into()).unwrap();
+        store.set_todo_count(id, Some(2)).unwrap();
 
-    tx.commit()?;
-    
-    Ok(())
-}
+        store.decrement_todo(id).unwrap();
+        assert!(!todo(&store, id).completed);
+        store.decrement_todo(id).unwrap();
+        assert!(todo(&store, id).completed);
+        assert_eq!(completions(&store, id), 1);
 
-pub fn set_todo_count(app_handle: &AppHandle, id: u32, count: Option<i32>) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    let current_count = count.unwrap_or(0);
-    
-    conn.execute(
-        "UPDATE todos SET target_count = ?1, current_count = ?2 WHERE id = ?3",
-        params![count, current_count, id],
-    )?;
-    
-    Ok(())
-}
+        // Already at zero: no new events, no second recurrence
+        store.decrement_todo(id).unwrap();
+        assert_eq!(completions(&store, id), 1);
+    }
 
-pub fn decrement_todo(app_handle: &AppHandle, id: u32) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    // Decrement count
-    conn.execute(
-        "UPDATE todos SET current_count = current_count - 1 WHERE id = ? AND current_count > 0",
-        params![id],
-    )?;
-    
-    // Check if reached 0
-    let current_count: i32 = conn.query_row(
-        "SELECT current_count FROM todos WHERE id = ?",
-        params![id],
-        |row| row.get(0),
-    )?;
-    
-    if current_count <= 0 {
-        // Mark as completed and trigger cascade
-        update_todo(app_handle, id, true)?;
+    #[test]
+    fn decrement_leaves_plain_items_alone() {
+        let store = store();
+        let id = store.save_todo(1, "Plain".into()).unwrap();
+        store.decrement_todo(id).unwrap();
+        assert!(!todo(&store, id).completed);
+        assert_eq!(completions(&store, id), 0);
+        assert!(store.decrement_todo(999).is_err());
     }
-    
-    Ok(())
-}
 
-pub fn reset_all_todos(app_handle: &AppHandle) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    // Reset all todos to incomplete and reset countdown
-    conn.execute(
-        "UPDATE todos SET completed = 0, current_count = COALESCE(target_count, 0)",
-        [],
-    )?;
-    
-    Ok(())
-}
+    #[test]
+    fn parents_completed_by_their_children_recur() {
+        let store = store();
+        let parent = store.save_todo(1, "Weekly review".into()).unwrap();
+        let child = store.save_todo(1, "Inbox zero".into()).unwrap();
+        store.move_todo(child, Some(parent), 0).unwrap();
+        let rule = RecurrenceRule { repeat: recurrence::Repeat::Daily, mode: RecurrenceMode::Reschedule };
+        store.set_todo_recurrence(parent, Some(rule)).unwrap();
 
-pub fn save_window_state(
-    app_handle: &AppHandle,
-    width: f64,
-    height: f64,
-    x: f64,
-    y: f64,
-    pinned: bool,
-) -> Result<()> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    // Use INSERT OR REPLACE to upsert
-    conn.execute(
-        "INSERT OR REPLACE INTO window_state (id, width, height, x, y, pinned) VALUES (1, ?, ?, ?, ?, ?)",
-        params![width, height, x, y, if pinned { 1 } else { 0 }],
-    )?;
-    
-    Ok(())
-}
+        store.update_todo(child, true).unwrap();
+        let parent_item = todo(&store, parent);
+        assert!(!parent_item.completed);
+        assert!(!todo(&store, child).completed);
+        assert!(parent_item.due_at.is_some());
 
-pub fn load_window_state(app_handle: &AppHandle) -> Result<Option<WindowState>> {
-    let app_dir = app_handle.path().app_data_dir().unwrap();
-    let db_path = app_dir.join("sticky_notes.db");
-    let conn = Connection::open(db_path)?;
-    
-    let result = conn.query_row(
-        "SELECT width, height, x, y, pinned FROM window_state WHERE id = 1",
-        [],
-        |row| {
-            Ok(WindowState {
-                width: row.get(0)?,
-                height: row.get(1)?,
-                x: row.get(2)?,
-                y: row.get(3)?,
-                pinned: row.get::<_, i32>(4)? != 0,
-            })
-        },
-    );
-    
-    match result {
-        Ok(state) => Ok(Some(state)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e),
+        // Spawning leaves the finished occurrence and adds the next one
+        let rule = RecurrenceRule { repeat: recurrence::Repeat::Daily, mode: RecurrenceMode::Spawn };
+        store.set_todo_recurrence(parent, Some(rule)).unwrap();
+        store.update_todo(child, true).unwrap();
+        let todos = store.get_todos(1).unwrap();
+        assert!(todo(&store, parent).completed);
+        assert_eq!(todos.iter().filter(|t| t.text == "Weekly review").count(), 2);
+        assert!(todos.iter().any(|t| t.text == "Weekly review" && !t.completed && t.recurrence.is_some()));
+    }
+
+    #[test]
+    fn stats_reject_spans_past_the_limit() {
+        let store = store();
+        assert!(store.get_stats(stats::MAX_DAYS).is_ok());
+        assert!(matches!(store.get_stats(u32::MAX), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn reparenting_delete_moves_children_into_its_place() {
+        let store = store();
+        let a = store.save_todo(1, "A".into()).unwrap();
+        let p = store.save_todo(1, "P".into()).unwrap();
+        let z = store.save_todo(1, "Z".into()).unwrap();
+        let c1 = store.save_todo(1, "C1".into()).unwrap();
+        let c2 = store.save_todo(1, "C2".into()).unwrap();
+        store.move_todo(c1, Some(p), 0).unwrap();
+        store.move_todo(c2, Some(p), 1).unwrap();
+
+        store.delete_todo(p, DeleteMode::Reparent).unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, None, 0), (z, None, 3), (c1, None, 1), (c2, None, 2)]);
+        let trash = store.get_trash().unwrap();
+        assert_eq!((trash[0].id, trash[0].item_count), (p, 1));
+
+        // Back without its children, which stay where they went
+        store.restore_todo(p).unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, None, 0), (p, None, 1), (z, None, 4), (c1, None, 2), (c2, None, 3)]);
+    }
+
+    #[test]
+    fn trash_restores_subtrees_and_compacts_positions() {
+        let store = store();
+        let a = store.save_todo(1, "A".into()).unwrap();
+        let p = store.save_todo(1, "P".into()).unwrap();
+        let z = store.save_todo(1, "Z".into()).unwrap();
+        let c = store.save_todo(1, "C".into()).unwrap();
+        store.move_todo(c, Some(p), 0).unwrap();
+
+        store.delete_todo(p, DeleteMode::Cascade).unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, None, 0), (z, None, 1)]);
+        assert_eq!(store.get_trash().unwrap()[0].item_count, 2);
+
+        store.restore_todo(p).unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, None, 0), (p, None, 1), (z, None, 2), (c, Some(p), 0)]);
+        assert!(store.get_trash().unwrap().is_empty());
+        assert!(matches!(store.restore_todo(p), Err(AppError::NotFound(_))));
+
+        // Without its parent, an item comes back at the end of the top level
+        store.delete_todo(c, DeleteMode::Cascade).unwrap();
+        store.delete_todo(p, DeleteMode::Cascade).unwrap();
+        store.restore_todo(c).unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, None, 0), (z, None, 1), (c, None, 2)]);
+    }
+
+    fn hits(store: &TodoStore, query: &str) -> Vec<(HitKind, u32)> {
+        store.search(query, 10).unwrap().into_iter().map(|h| (h.kind, h.id)).collect()
+    }
+
+    #[test]
+    fn search_follows_edits_and_deletes() {
+        let store = store();
+        let id = store.save_todo(1, "Buy milk".into()).unwrap();
+        assert_eq!(hits(&store, "mil"), vec![(HitKind::Todo, id)]);
+
+        store.update_todo_text(id, "Buy bread".into()).unwrap();
+        assert!(hits(&store, "milk").is_empty());
+        assert_eq!(hits(&store, "bread"), vec![(HitKind::Todo, id)]);
+        store.undo().unwrap();
+        assert_eq!(hits(&store, "milk"), vec![(HitKind::Todo, id)]);
+
+        store.delete_todo(id, DeleteMode::Cascade).unwrap();
+        store.purge_todo(id).unwrap();
+        assert!(hits(&store, "milk").is_empty());
+
+        let note = store.create_note("Plans".into()).unwrap();
+        store.save_note(note, "Meeting agenda".into()).unwrap();
+        assert_eq!(hits(&store, "agenda"), vec![(HitKind::Note, note)]);
+        store.save_note(note, "Nothing".into()).unwrap();
+        assert!(hits(&store, "agenda").is_empty());
+    }
+
+    #[test]
+    fn search_skips_trash_and_archive() {
+        let store = store();
+        let trashed = store.save_todo(1, "Paint fence".into()).unwrap();
+        let archived = store.save_todo(1, "Paint door".into()).unwrap();
+        store.delete_todo(trashed, DeleteMode::Cascade).unwrap();
+        store.update_todo(archived, true).unwrap();
+        store.archive_todo(archived).unwrap();
+        assert!(hits(&store, "paint").is_empty());
+
+        store.restore_todo(trashed).unwrap();
+        store.restore_archived(archived).unwrap();
+        assert_eq!(hits(&store, "paint").len(), 2);
+    }
+
+    #[test]
+    fn search_takes_operators_literally() {
+        let store = store();
+        store.save_todo(1, "Call \"Bob\" (NEAR the office) OR email".into()).unwrap();
+        for query in ["\"bob", "bob\"", "NEAR(", "OR", "call AND", "-office", "text:call", "(call", "bob*", "^call"] {
+            assert!(store.search(query, 10).is_ok(), "{}", query);
+        }
+        assert_eq!(hits(&store, "OR email").len(), 1);
+        assert_eq!(hits(&store, "\"bob\"").len(), 1);
+    }
+
+    #[test]
+    fn imported_checked_items_have_a_completion_time() {
+        let store = store();
+        store.import_markdown(1, None, "- [x] Done\n- [ ] Open\n").unwrap();
+        let completed_at: Vec<(String, Option<i64>)> = store
+            .conn()
+            .prepare("SELECT text, completed_at FROM todos ORDER BY position")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(completed_at[0].0, "Done");
+        assert!(completed_at[0].1.is_some());
+        assert_eq!(completed_at[1].1, None);
+
+        // Not old enough yet, until it is
+        store.set_auto_archive_days(1).unwrap();
+        assert_eq!(store.run_auto_archive().unwrap(), 0);
+        store.conn().execute("UPDATE todos SET completed_at = 0 WHERE text = 'Done'", []).unwrap();
+        assert_eq!(store.run_auto_archive().unwrap(), 1);
+    }
+
+    #[test]
+    fn reopens_a_database_file() {
+        let path = std::env::temp_dir().join(format!("sticky-notes-test-{}.db", std::process::id()));
+        let store = TodoStore::open(&path).unwrap();
+        let id = store.save_todo(1, "Kept".into()).unwrap();
+        drop(store);
+
+        let store = TodoStore::open(&path).unwrap();
+        assert_eq!(todo(&store, id).text, "Kept");
+        let foreign_keys: bool = store.conn().pragma_query_value(None, "foreign_keys", |row| row.get(0)).unwrap();
+        assert!(foreign_keys);
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn checklists_round_trip() {
+        let store = store();
+        let markdown = "- [ ] Trip !high\n  - [x] Tickets\n  - [ ] Hotel\n    - [ ] Book !urgent (1/3)\n- [x] Laundry\n";
+        store.import_markdown(1, None, markdown).unwrap();
+        assert_eq!(store.export_markdown(1).unwrap(), markdown);
+
+        assert!(store.undo().unwrap().is_some());
+        assert_eq!(store.export_markdown(1).unwrap(), "");
+    }
+
+    #[test]
+    fn backups_restore_by_replacing_or_merging() {
+        let store = store();
+        let work = store.create_list("Work".into()).unwrap();
+        let parent = store.save_todo(work, "Release #ship".into()).unwrap();
+        let child = store.save_todo(work, "Tests".into()).unwrap();
+        store.move_todo(child, Some(parent), 0).unwrap();
+        store.update_todo(child, true).unwrap();
+        let note = store.create_note("Second".into()).unwrap();
+        store.save_note(note, "Body".into()).unwrap();
+        let backup = Backup::from_json(&store.export_backup().unwrap().to_json().unwrap()).unwrap();
+
+        // Replace keeps the ids and starts a fresh undo history
+        let other = TodoStore::open_in_memory().unwrap();
+        other.save_todo(1, "Dropped".into()).unwrap();
+        let summary = other.restore_backup(&backup, RestoreMode::Replace).unwrap();
+        assert_eq!((summary.lists, summary.todos, summary.notes), (2, 2, 2));
+        assert_eq!(other.export_markdown(1).unwrap(), "");
+        assert_eq!(other.export_markdown(work).unwrap(), store.export_markdown(work).unwrap());
+        assert_eq!(other.get_note(note).unwrap().content, "Body");
+        assert_eq!(other.get_tags().unwrap()[0].name, "ship");
+        assert_eq!(other.undo().unwrap(), None);
+
+        // Merge adds copies under new ids
+        store.restore_backup(&backup, RestoreMode::Merge).unwrap();
+        let lists = store.get_lists().unwrap();
+        assert_eq!(lists.len(), 4);
+        let copy = lists.last().unwrap().id;
+        assert_ne!(copy, work);
+        assert_eq!(store.export_markdown(copy).unwrap(), store.export_markdown(work).unwrap());
+    }
+
+    fn journal_sizes(store: &TodoStore) -> Vec<usize> {
+        store
+            .conn()
+            .prepare("SELECT changes FROM history ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .map(|changes| serde_json::from_str::<Vec<serde_json::Value>>(&changes.unwrap()).unwrap().len())
+            .collect()
+    }
+
+    #[test]
+    fn journal_holds_only_touched_rows() {
+        let store = store();
+        let first = store.save_todo(1, "First".into()).unwrap();
+        store.save_todo(1, "Second".into()).unwrap();
+        store.update_todo_text(first, "First, renamed".into()).unwrap();
+        assert_eq!(journal_sizes(&store).last(), Some(&1));
+
+        // A change that changes nothing is not recorded
+        let entries = journal_sizes(&store).len();
+        store.update_todo_text(first, "First, renamed".into()).unwrap();
+        assert_eq!(journal_sizes(&store).len(), entries);
+
+        // Nor are writes outside the journal
+        store.conn().execute("UPDATE todos SET text = 'Quiet' WHERE id = ?1", params![first]).unwrap();
+        assert_eq!(journal_sizes(&store).len(), entries);
+    }
+
+    #[test]
+    fn undo_round_trips() {
+        let store = store();
+        let id = store.save_todo(1, "Parent".into()).unwrap();
+        let child = store.save_todo(1, "Child".into()).unwrap();
+        store.move_todo(child, Some(id), 0).unwrap();
+        store.delete_todo(id, DeleteMode::Cascade).unwrap();
+        assert!(store.get_todos(1).unwrap().is_empty());
+
+        assert!(store.undo().unwrap().is_some());
+        assert_eq!(todo(&store, child).parent_id, Some(id));
+        assert!(store.redo().unwrap().is_some());
+        assert!(store.get_todos(1).unwrap().is_empty());
+        assert!(store.undo().unwrap().is_some());
+
+        // A new change discards what was undone
+        store.save_todo(1, "Other".into()).unwrap();
+        assert_eq!(store.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn undo_restores_sibling_positions_after_a_move() {
+        let store = store();
+        let a = store.save_todo(1, "A".into()).unwrap();
+        let b = store.save_todo(1, "B".into()).unwrap();
+        let c = store.save_todo(1, "C".into()).unwrap();
+        let before = tree(&store, 1);
+        assert_eq!(before, vec![(a, None, 0), (b, None, 1), (c, None, 2)]);
+
+        store.move_todo(a, Some(c), 0).unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, Some(c), 0), (b, None, 0), (c, None, 1)]);
+        store.move_todo(c, None, 0).unwrap();
+
+        store.undo().unwrap();
+        store.undo().unwrap();
+        assert_eq!(tree(&store, 1), before);
+        store.redo().unwrap();
+        assert_eq!(tree(&store, 1), vec![(a, Some(c), 0), (b, None, 0), (c, None, 1)]);
+    }
+
+    #[test]
+    fn undo_does_not_revert_an_unjournaled_reset() {
+        let store = store();
+        let id = store.save_todo(1, "Stretch".into()).unwrap();
+        store.update_todo(id, true).unwrap();
+        store.add_reset_schedule(1, None, ResetFrequency::Daily, "00:00".into()).unwrap();
+        store.conn().execute("UPDATE reset_schedules SET last_reset_at = 0", []).unwrap();
+        assert_eq!(store.run_due_resets().unwrap(), 1);
+
+        // Undoing the completion would write the pre-reset row back
+        assert!(matches!(store.undo(), Err(AppError::ConstraintViolation(_))));
+        assert!(!todo(&store, id).completed);
+    }
+
+    #[test]
+    fn undo_of_purged_rows_fails_and_drops_the_entry() {
+        let store = store();
+        let id = store.save_todo(1, "Old".into()).unwrap();
+        store.delete_todo(id, DeleteMode::Cascade).unwrap();
+        store.conn().execute("UPDATE todos SET deleted_at = 0 WHERE id = ?1", params![id]).unwrap();
+        assert_eq!(store.purge_expired_trash().unwrap(), 1);
+
+        assert!(matches!(store.undo(), Err(AppError::ConstraintViolation(_))));
+        assert_eq!(store.undo().unwrap(), None);
+        let left: i64 = store.conn().query_row("SELECT COUNT(*) FROM todos", [], |row| row.get(0)).unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn scheduled_resets_leave_undo_and_redo_alone() {
+        let store = store();
+        let other = store.create_list("Chores".into()).unwrap();
+        let id = store.save_todo(1, "Water plants".into()).unwrap();
+        store.update_todo(id, true).unwrap();
+        store.update_todo(id, false).unwrap();
+        store.undo().unwrap();
+        assert!(todo(&store, id).completed);
+
+        // The reset runs on another list, so the redo still fits
+        store.add_reset_schedule(other, None, ResetFrequency::Daily, "00:00".into()).unwrap();
+        store.conn().execute("UPDATE reset_schedules SET last_reset_at = 0", []).unwrap();
+        assert_eq!(store.run_due_resets().unwrap(), 1);
+
+        let entries = journal_sizes(&store).len();
+        assert_eq!(store.redo().unwrap().as_deref(), Some("Uncheck item"));
+        assert_eq!(journal_sizes(&store).len(), entries);
+
+        // Undoing everything keeps the reset in the statistics
+        while store.undo().unwrap().is_some() {}
+        let events = store.get_todo_events(None, None, None).unwrap();
+        assert!(events.iter().any(|e| e.kind == EventKind::Reset));
+    }
+
+    #[test]
+    fn auto_archive_leaves_undo_and_redo_alone() {
+        let store = store();
+        let done = store.save_todo(1, "Done".into()).unwrap();
+        let other = store.save_todo(1, "Other".into()).unwrap();
+        store.update_todo(done, true).unwrap();
+        store.update_todo_text(other, "Other, renamed".into()).unwrap();
+        store.undo().unwrap();
+
+        store.set_auto_archive_days(1).unwrap();
+        store.conn().execute("UPDATE todos SET completed_at = 0 WHERE id = ?1", params![done]).unwrap();
+        let entries = journal_sizes(&store).len();
+        assert_eq!(store.run_auto_archive().unwrap(), 1);
+        assert_eq!(journal_sizes(&store).len(), entries);
+        assert_eq!(store.redo().unwrap().as_deref(), Some("Edit text"));
+        assert_eq!(store.get_archive().unwrap().len(), 1);
     }
 }
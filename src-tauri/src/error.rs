@@ -0,0 +1,64 @@
+use crate::migrations::MigrationError;
+use rusqlite::ErrorCode;
+use serde::Serialize;
+use std::fmt;
+
+/// Error returned by every command. Serialized as
+/// `{ "kind": "not_found", "message": "..." }` so the UI can branch on `kind`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum AppError {
+    NotFound(String),
+    ConstraintViolation(String),
+    InvalidInput(String),
+    Io(String),
+    Migration(String),
+    Database(String),
+}
+
+pub type Result<T, E = AppError> = std::result::Result<T, E>;
+
+impl AppError {
+    pub fn not_found(what: impl fmt::Display) -> Self {
+        AppError::NotFound(format!("{} not found", what))
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound(msg)
+            | AppError::ConstraintViolation(msg)
+            | AppError::InvalidInput(msg)
+            | AppError::Io(msg)
+            | AppError::Migration(msg)
+            | AppError::Database(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(e.to_string()),
+            rusqlite::Error::SqliteFailure(err, _) if err.code == ErrorCode::ConstraintViolation => {
+                AppError::ConstraintViolation(e.to_string())
+            }
+            _ => AppError::Database(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io(e.to_string())
+    }
+}
+
+impl From<MigrationError> for AppError {
+    fn from(e: MigrationError) -> Self {
+        AppError::Migration(e.to_string())
+    }
+}
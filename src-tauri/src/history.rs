@@ -0,0 +1,330 @@
+//! Undo/redo journal.
+//!
+//! Every mutation made through `TodoStore` runs in one transaction. While
+//! it runs, temporary triggers on the tracked tables copy the before and
+//! after image of each row a statement touches into `journal_changes`, so
+//! the cost follows the size of the change, not of the database. The net
+//! change per row is stored in the `history` table, down to the columns that
+//! changed for updated rows. Undo writes the before images back, redo writes
+//! the after images, so cascades, subtree deletes and position shifts all
+//! invert the same way. A row that no longer matches the other image was
+//! changed outside the journal since, and the entry is dropped rather than
+//! overwriting that change.
+
+use crate::error::Result;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+use std::collections::HashMap;
+
+/// Tables whose rows are journaled.
+const TRACKED_TABLES: &[&str] = &["lists", "todos", "tags", "todo_tags"];
+
+/// How many entries are kept before the oldest are dropped.
+const MAX_ENTRIES: i64 = 500;
+
+type Row = Map<String, Json>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Change {
+    table: String,
+    rowid: i64,
+    before: Option<Row>,
+    after: Option<Row>,
+}
+
+/// Which side of a journal entry to write back.
+#[derive(Clone, Copy)]
+enum Side {
+    Before,
+    After,
+}
+
+/// Outcome of an undo or redo.
+pub enum Step {
+    /// Nothing left to undo or redo.
+    Nothing,
+    /// The entry with this id and label was applied.
+    Applied(i64, String),
+    /// The entry no longer fits the data, e.g. its rows were purged since,
+    /// so it was dropped together with the entries behind it.
+    Dropped(String),
+}
+
+/// Creates the capture table and triggers on `conn`. They are temporary,
+/// so this runs on every open, after the migrations have settled the
+/// columns of the tracked tables.
+pub fn install(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TEMP TABLE journal_changes (
+            seq INTEGER PRIMARY KEY,
+            tbl TEXT NOT NULL,
+            row_id INTEGER NOT NULL,
+            before TEXT,
+            after TEXT
+        );
+        -- Holds a row while a journaled transaction runs
+        CREATE TEMP TABLE journal_active (on_ INTEGER);",
+    )?;
+
+    for &table in TRACKED_TABLES {
+        let columns = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let image = |row: &str| {
+            let pairs: Vec<String> = columns.iter().map(|c| format!("'{c}', {row}.\"{c}\"")).collect();
+            format!("json_object({})", pairs.join(", "))
+        };
+
+        for (event, row_id, before, after) in [
+            ("INSERT", "new.rowid", "NULL".to_string(), image("new")),
+            ("UPDATE", "new.rowid", image("old"), image("new")),
+            ("DELETE", "old.rowid", image("old"), "NULL".to_string()),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE TEMP TRIGGER journal_{table}_{name} AFTER {event} ON main.{table}
+                     WHEN EXISTS (SELECT 1 FROM journal_active)
+                     BEGIN
+                        INSERT INTO journal_changes (tbl, row_id, before, after)
+                        VALUES ('{table}', {row_id}, {before}, {after});
+                     END",
+                    name = event.to_lowercase(),
+                ),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Starts capturing row changes for the journaled transaction `tx`. Should
+/// the transaction roll back, the capture goes with it.
+pub fn begin(tx: &Transaction) -> Result<()> {
+    tx.execute_batch("DELETE FROM temp.journal_changes; INSERT INTO temp.journal_active VALUES (1);")?;
+    Ok(())
+}
+
+/// Stops capturing and stores the net change of every touched row under
+/// `label`. Discards the redo stack. Returns the new entry's id, or `None`
+/// if no row changed and nothing was stored.
+pub fn record(tx: &Transaction, label: &str) -> Result<Option<i64>> {
+    tx.execute("DELETE FROM temp.journal_active", [])?;
+
+    let captured = tx
+        .prepare("SELECT tbl, row_id, before, after FROM temp.journal_changes ORDER BY seq")?
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    tx.execute("DELETE FROM temp.journal_changes", [])?;
+
+    // A row touched several times keeps its first before and last after image
+    let mut changes: Vec<Change> = Vec::new();
+    let mut index: HashMap<(String, i64), usize> = HashMap::new();
+    for (table, rowid, before, after) in captured {
+        let parse = |image: Option<String>| -> Result<Option<Row>> {
+            image.map(|i| serde_json::from_str(&i).map_err(json_error)).transpose()
+        };
+        let after = parse(after)?;
+        match index.get(&(table.clone(), rowid)) {
+            Some(&i) => changes[i].after = after,
+            None => {
+                index.insert((table.clone(), rowid), changes.len());
+                changes.push(Change { table, rowid, before: parse(before)?, after });
+            }
+        }
+    }
+    changes.retain(|c| c.before != c.after);
+    for change in &mut changes {
+        if let (Some(before), Some(after)) = (&mut change.before, &mut change.after) {
+            let unchanged: Vec<String> =
+                before.iter().filter(|(c, v)| after.get(*c) == Some(*v)).map(|(c, _)| c.clone()).collect();
+            for column in unchanged {
+                before.remove(&column);
+                after.remove(&column);
+            }
+        }
+    }
+
+    if changes.is_empty() {
+        return Ok(None);
+    }
+
+    tx.execute("DELETE FROM history WHERE undone = 1", [])?;
+    tx.execute(
+        "INSERT INTO history (label, changes) VALUES (?1, ?2)",
+        params![label, serde_json::to_string(&changes).map_err(json_error)?],
+    )?;
+    let id = tx.last_insert_rowid();
+    tx.execute(
+        "DELETE FROM history WHERE id <= (SELECT id FROM history ORDER BY id DESC LIMIT 1 OFFSET ?1)",
+        params![MAX_ENTRIES],
+    )?;
+    Ok(Some(id))
+}
+
+/// Reverts the most recent entry.
+pub fn undo(tx: &Transaction) -> Result<Step> {
+    step(tx, Side::Before)
+}
+
+/// Re-applies the oldest undone entry.
+pub fn redo(tx: &Transaction) -> Result<Step> {
+    step(tx, Side::After)
+}
+
+fn step(tx: &Transaction, side: Side) -> Result<Step> {
+    let query = match side {
+        Side::Before => "SELECT id, label, changes FROM history WHERE undone = 0 ORDER BY id DESC LIMIT 1",
+        Side::After => "SELECT id, label, changes FROM history WHERE undone = 1 ORDER BY id ASC LIMIT 1",
+    };
+    let entry = tx
+        .query_row(query, [], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })
+        .optional()?;
+    let Some((id, label, changes)) = entry else {
+        return Ok(Step::Nothing);
+    };
+
+    tx.execute_batch("SAVEPOINT history_step")?;
+    if !apply(tx, &changes, side)? {
+        // Something outside the journal, like the trash purge, changed these
+        // rows. The entry and the ones that build on it cannot be applied.
+        tx.execute_batch("ROLLBACK TO history_step; RELEASE history_step")?;
+        match side {
+            Side::Before => tx.execute("DELETE FROM history WHERE undone = 0 AND id <= ?1", params![id])?,
+            Side::After => tx.execute("DELETE FROM history WHERE undone = 1", [])?,
+        };
+        return Ok(Step::Dropped(label));
+    }
+    tx.execute_batch("RELEASE history_step")?;
+
+    let undone = matches!(side, Side::Before);
+    tx.execute("UPDATE history SET undone = ?1 WHERE id = ?2", params![undone, id])?;
+    Ok(Step::Applied(id, label))
+}
+
+/// Writes one side of an entry back. Returns `false`, leaving the caller to
+/// roll back, as soon as a row is not where the entry expects it: changed
+/// since by something outside the journal, like a scheduled reset.
+fn apply(tx: &Transaction, changes: &str, side: Side) -> Result<bool> {
+    let changes: Vec<Change> = serde_json::from_str(changes).map_err(json_error)?;
+
+    // Restored children may be written before their parents
+    tx.pragma_update(None, "defer_foreign_keys", true)?;
+
+    let mut deletes = Vec::new();
+    for change in &changes {
+        let (current, target) = match side {
+            Side::Before => (&change.after, &change.before),
+            Side::After => (&change.before, &change.after),
+        };
+        let applied = match (current, target) {
+            (Some(expected), Some(image)) => {
+                live_row(tx, &change.table, change.rowid, expected)?.as_ref() == Some(expected)
+                    && update_row(tx, &change.table, change.rowid, image)?
+            }
+            (None, Some(image)) => insert_row(tx, &change.table, change.rowid, image)?,
+            // A row that is gone already needs no deleting
+            (Some(expected), None) => {
+                deletes.push(change);
+                live_row(tx, &change.table, change.rowid, expected)?.is_none_or(|row| row == *expected)
+            }
+            (None, None) => true,
+        };
+        if !applied {
+            return Ok(false);
+        }
+    }
+
+    // Delete last so restored rows are never caught by a cascade. A row a
+    // cascade already took counts as deleted.
+    for change in deletes {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE rowid = ?1", change.table),
+            params![change.rowid],
+        )?;
+    }
+
+    // A restored row may point at one that was purged since
+    let mut tables: Vec<&str> = changes.iter().map(|c| c.table.as_str()).collect();
+    tables.dedup();
+    for table in tables {
+        let dangling: bool =
+            tx.query_row("SELECT EXISTS (SELECT 1 FROM pragma_foreign_key_check(?1))", params![table], |row| {
+                row.get(0)
+            })?;
+        if dangling {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The row as it is now, in the columns of `image`.
+fn live_row(tx: &Transaction, table: &str, rowid: i64, image: &Row) -> Result<Option<Row>> {
+    let pairs: Vec<String> = image.keys().map(|c| format!("'{c}', \"{c}\"")).collect();
+    let row = tx
+        .query_row(
+            &format!("SELECT json_object({}) FROM {} WHERE rowid = ?1", pairs.join(", "), table),
+            params![rowid],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+    row.map(|row| serde_json::from_str(&row).map_err(json_error)).transpose()
+}
+
+fn update_row(tx: &Transaction, table: &str, rowid: i64, image: &Row) -> Result<bool> {
+    let assignments: Vec<String> = image.keys().map(|c| format!("\"{}\" = ?", c)).collect();
+    let mut values: Vec<Value> = image.values().map(from_json).collect();
+    values.push(Value::Integer(rowid));
+    let updated = tx.execute(
+        &format!("UPDATE {} SET {} WHERE rowid = ?", table, assignments.join(", ")),
+        rusqlite::params_from_iter(values),
+    )?;
+    Ok(updated == 1)
+}
+
+fn insert_row(tx: &Transaction, table: &str, rowid: i64, image: &Row) -> Result<bool> {
+    let columns: Vec<String> = image.keys().map(|c| format!("\"{}\"", c)).collect();
+    let placeholders = vec!["?"; columns.len() + 1].join(", ");
+    let mut values = vec![Value::Integer(rowid)];
+    values.extend(image.values().map(from_json));
+    let inserted = tx.execute(
+        &format!("INSERT INTO {} (rowid, {}) VALUES ({})", table, columns.join(", "), placeholders),
+        rusqlite::params_from_iter(values),
+    );
+    match inserted {
+        Ok(_) => Ok(true),
+        // The row is back already, through some other way
+        Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == ErrorCode::ConstraintViolation => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn from_json(value: &Json) -> Value {
+    match value {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Integer(*b as i64),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        Json::String(s) => Value::Text(s.clone()),
+        Json::Array(items) => Value::Blob(items.iter().filter_map(|i| i.as_u64()).map(|b| b as u8).collect()),
+        Json::Object(_) => Value::Null,
+    }
+}
+
+fn json_error(e: serde_json::Error) -> crate::error::AppError {
+    crate::error::AppError::Database(format!("corrupt history entry: {}", e))
+}
@@ -1,5 +1,6 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::DialogExt;
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
@@ -15,6 +16,13 @@ fn set_always_on_top(window: tauri::Window, always_on_top: bool) {
 
 #[tauri::command]
 fn close_window(window: tauri::Window) {
+    // A note window closed by hand stays closed, unlike one that goes
+    // away with the app
+    if let (Some(id), Some(store)) = (window_note_id(window.label()), window.try_state::<db::TodoStore>()) {
+        if let Err(e) = store.close_note_window(id) {
+            println!("[Notes] Failed to forget window of note {}: {}", id, e);
+        }
+    }
     let _ = window.close();
 }
 
@@ -23,50 +31,346 @@ fn start_drag(window: tauri::Window) {
     let _ = window.start_dragging();
 }
 
-mod db;
+pub mod backup;
+mod checklist;
+pub mod db;
+pub mod error;
+mod history;
+mod markers;
+pub mod migrations;
+pub mod query;
+pub mod recurrence;
+pub mod revisions;
+pub mod schedule;
+pub mod search;
+pub mod stats;
+
+use error::AppError;
+
+/// Set when the database could not be opened or upgraded at startup, so the
+/// UI can explain why it is empty instead of the app failing to launch.
+struct StartupError(Option<AppError>);
+
+#[tauri::command]
+fn startup_error(state: tauri::State<'_, StartupError>) -> Option<AppError> {
+    state.0.clone()
+}
+
+/// Label of the sticky window a note has of its own.
+fn note_window_label(id: u32) -> String {
+    format!("note-{}", id)
+}
+
+/// The note a window was opened for, `None` for the main window.
+fn window_note_id(label: &str) -> Option<u32> {
+    label.strip_prefix("note-")?.parse().ok()
+}
+
+fn build_note_window(app: &tauri::AppHandle, id: u32, title: &str, state: &db::WindowState) -> tauri::Result<()> {
+    let builder = tauri::WebviewWindowBuilder::new(app, note_window_label(id), tauri::WebviewUrl::App("index.html".into()))
+        .title(title)
+        .inner_size(state.width, state.height)
+        .position(state.x, state.y)
+        .decorations(false)
+        .always_on_top(state.pinned)
+        .shadow(false);
+    // Same look as the main window, see tauri.conf.json
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+    builder.build()?;
+    Ok(())
+}
+
+#[tauri::command]
+fn load_notes(store: tauri::State<'_, db::TodoStore>) -> Result<Vec<db::NoteSummary>, AppError> {
+    store.get_notes()
+}
+
+#[tauri::command]
+fn load_note(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<db::Note, AppError> {
+    store.get_note(id)
+}
+
+#[tauri::command]
+fn save_note_content(store: tauri::State<'_, db::TodoStore>, id: u32, content: String) -> Result<(), AppError> {
+    store.save_note(id, content)
+}
+
+#[tauri::command]
+fn load_note_revisions(store: tauri::State<'_, db::TodoStore>, note_id: u32) -> Result<Vec<revisions::NoteRevision>, AppError> {
+    store.get_note_revisions(note_id)
+}
+
+#[tauri::command]
+fn diff_note_revision(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<Vec<revisions::DiffLine>, AppError> {
+    store.diff_note_revision(id)
+}
+
+#[tauri::command]
+fn restore_note_revision(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<u32, AppError> {
+    store.restore_note_revision(id)
+}
+
+#[tauri::command]
+fn create_note(store: tauri::State<'_, db::TodoStore>, title: String) -> Result<u32, AppError> {
+    store.create_note(title)
+}
+
+#[tauri::command]
+fn rename_note(store: tauri::State<'_, db::TodoStore>, id: u32, title: String) -> Result<(), AppError> {
+    store.rename_note(id, title)
+}
+
+#[tauri::command]
+fn move_note(store: tauri::State<'_, db::TodoStore>, id: u32, position: i32) -> Result<(), AppError> {
+    store.move_note(id, position)
+}
+
+#[tauri::command]
+fn delete_note(app: tauri::AppHandle, store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.delete_note(id)?;
+    if let Some(window) = app.get_webview_window(&note_window_label(id)) {
+        let _ = window.close();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_active_note(store: tauri::State<'_, db::TodoStore>) -> Result<u32, AppError> {
+    store.active_note()
+}
+
+#[tauri::command]
+fn set_active_note(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.set_active_note(id)
+}
+
+/// Shows a note in a sticky window of its own, or focuses the one it has.
+// Async so the window is not created on the main thread, which can deadlock
+// on Windows.
+#[tauri::command]
+async fn open_note_window(app: tauri::AppHandle, store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    if let Some(window) = app.get_webview_window(&note_window_label(id)) {
+        let _ = window.set_focus();
+        return Ok(());
+    }
+    let note = store.get_note(id)?;
+    let state = store.open_note_window(id)?;
+    build_note_window(&app, id, &note.title, &state).map_err(|e| AppError::Io(e.to_string()))
+}
+
+/// The note the calling window was opened for, `None` in the main window.
+#[tauri::command]
+fn window_note(window: tauri::Window) -> Option<u32> {
+    window_note_id(window.label())
+}
+
+#[tauri::command]
+fn load_todos(store: tauri::State<'_, db::TodoStore>, list_id: u32) -> Result<Vec<db::TodoItem>, AppError> {
+    store.get_todos(list_id)
+}
+
+#[tauri::command]
+fn export_markdown(store: tauri::State<'_, db::TodoStore>, list_id: u32) -> Result<String, AppError> {
+    store.export_markdown(list_id)
+}
+
+/// Asks where to save a list as a Markdown checklist, suggesting a file
+/// named after the list, and writes it there. Returns the file's path, or
+/// `None` if the dialog was cancelled.
+#[tauri::command]
+async fn export_markdown_file(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, db::TodoStore>,
+    list_id: u32,
+) -> Result<Option<String>, AppError> {
+    let markdown = store.export_markdown(list_id)?;
+    let name = store
+        .get_lists()?
+        .into_iter()
+        .find(|l| l.id == list_id)
+        .map(|l| l.name)
+        .unwrap_or_default();
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') { c } else { '_' })
+        .collect();
+    let stem = if stem.trim().is_empty() { "todos".to_string() } else { stem.trim().to_string() };
+
+    save_export(&app, "Export checklist", &stem, ("Markdown", "md"), &markdown)
+}
+
+/// Asks where to save an export, starting in the downloads folder (or
+/// documents, if there is none) with `<stem>.<extension>` filled in, and
+/// writes `contents` there. Returns the file's path, or `None` if the
+/// dialog was cancelled. Blocks, so only call it from async commands,
+/// which run off the main thread.
+fn save_export(
+    app: &tauri::AppHandle,
+    title: &str,
+    stem: &str,
+    (kind, extension): (&str, &str),
+    contents: &str,
+) -> Result<Option<String>, AppError> {
+    let mut dialog = app
+        .dialog()
+        .file()
+        .set_title(title)
+        .set_file_name(format!("{}.{}", stem, extension))
+        .add_filter(kind, &[extension]);
+    if let Ok(dir) = app.path().download_dir().or_else(|_| app.path().document_dir()) {
+        dialog = dialog.set_directory(dir);
+    }
+    let Some(path) = dialog.blocking_save_file() else {
+        return Ok(None);
+    };
+    let path = path.into_path().map_err(|e| AppError::Io(e.to_string()))?;
+    std::fs::write(&path, contents)?;
+
+    Ok(Some(path.display().to_string()))
+}
+
+/// Asks where to save a full JSON backup and writes it there. Returns the
+/// file's path, or `None` if the dialog was cancelled.
+#[tauri::command]
+async fn export_backup(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, db::TodoStore>,
+) -> Result<Option<String>, AppError> {
+    let json = store.export_backup()?.to_json()?;
+    let stem = format!("sticky-notes-backup-{}", chrono::Local::now().format(db::DATE_FORMAT));
+    save_export(&app, "Export backup", &stem, ("Backup", "json"), &json)
+}
+
+/// Restores a backup read by the UI. After a replace the UI shows the
+/// summary and then calls `restart_app`, so every window comes back the way
+/// the backup has it.
+#[tauri::command]
+fn restore_backup(
+    store: tauri::State<'_, db::TodoStore>,
+    json: String,
+    mode: backup::RestoreMode,
+) -> Result<backup::RestoreSummary, AppError> {
+    let backup = backup::Backup::from_json(&json)?;
+    store.restore_backup(&backup, mode)
+}
+
+#[tauri::command]
+fn restart_app(app: tauri::AppHandle) {
+    app.restart();
+}
+
+/// Adds a pasted or opened Markdown checklist to a list, under `parent_id`
+/// when given.
+#[tauri::command]
+fn import_markdown(
+    store: tauri::State<'_, db::TodoStore>,
+    list_id: u32,
+    parent_id: Option<u32>,
+    markdown: String,
+) -> Result<Vec<u32>, AppError> {
+    store.import_markdown(list_id, parent_id, &markdown)
+}
+
+#[tauri::command]
+fn load_tags(store: tauri::State<'_, db::TodoStore>) -> Result<Vec<db::Tag>, AppError> {
+    store.get_tags()
+}
+
+#[tauri::command]
+fn add_todo_item(store: tauri::State<'_, db::TodoStore>, list_id: u32, text: String) -> Result<u32, AppError> {
+    store.save_todo(list_id, text)
+}
+
+#[tauri::command]
+fn update_todo_status(store: tauri::State<'_, db::TodoStore>, id: u32, completed: bool) -> Result<(), AppError> {
+    store.update_todo(id, completed)
+}
+
+#[tauri::command]
+fn update_todo_text(store: tauri::State<'_, db::TodoStore>, id: u32, text: String) -> Result<(), AppError> {
+    store.update_todo_text(id, text)
+}
+
+#[tauri::command]
+fn remove_todo_item(store: tauri::State<'_, db::TodoStore>, id: u32, keep_children: Option<bool>) -> Result<(), AppError> {
+    let mode = if keep_children.unwrap_or(false) {
+        db::DeleteMode::Reparent
+    } else {
+        db::DeleteMode::Cascade
+    };
+    store.delete_todo(id, mode)
+}
+
+#[tauri::command]
+fn load_trash(store: tauri::State<'_, db::TodoStore>) -> Result<Vec<db::TrashEntry>, AppError> {
+    store.get_trash()
+}
+
+#[tauri::command]
+fn restore_todo_item(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.restore_todo(id)
+}
+
+#[tauri::command]
+fn purge_todo_item(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.purge_todo(id)
+}
+
+#[tauri::command]
+fn empty_trash(store: tauri::State<'_, db::TodoStore>) -> Result<usize, AppError> {
+    store.empty_trash()
+}
+
+#[tauri::command]
+fn get_trash_retention_days(store: tauri::State<'_, db::TodoStore>) -> Result<u32, AppError> {
+    store.trash_retention_days()
+}
 
 #[tauri::command]
-fn load_note(app_handle: tauri::AppHandle) -> String {
-    db::get_note(&app_handle).unwrap_or_default()
+fn set_trash_retention_days(store: tauri::State<'_, db::TodoStore>, days: u32) -> Result<(), AppError> {
+    store.set_trash_retention_days(days)
 }
 
 #[tauri::command]
-fn save_note_content(app_handle: tauri::AppHandle, content: String) {
-    let _ = db::save_note(&app_handle, content);
+fn archive_todo_item(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.archive_todo(id)
 }
 
 #[tauri::command]
-fn load_todos(app_handle: tauri::AppHandle) -> Vec<db::TodoItem> {
-    db::get_todos(&app_handle).unwrap_or_default()
+fn load_archive(store: tauri::State<'_, db::TodoStore>) -> Result<Vec<db::ArchiveEntry>, AppError> {
+    store.get_archive()
 }
 
 #[tauri::command]
-fn add_todo_item(app_handle: tauri::AppHandle, text: String) -> u32 {
-    db::save_todo(&app_handle, text).unwrap_or(0)
+fn restore_archived_item(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.restore_archived(id)
 }
 
 #[tauri::command]
-fn update_todo_status(app_handle: tauri::AppHandle, id: u32, completed: bool) {
-    let _ = db::update_todo(&app_handle, id, completed);
+fn purge_archived_item(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.purge_archived(id)
 }
 
 #[tauri::command]
-fn update_todo_text(app_handle: tauri::AppHandle, id: u32, text: String) {
-    let _ = db::update_todo_text(&app_handle, id, text);
+fn get_auto_archive_days(store: tauri::State<'_, db::TodoStore>) -> Result<u32, AppError> {
+    store.auto_archive_days()
 }
 
 #[tauri::command]
-fn remove_todo_item(app_handle: tauri::AppHandle, id: u32) {
-    let _ = db::delete_todo(&app_handle, id);
+fn set_auto_archive_days(store: tauri::State<'_, db::TodoStore>, days: u32) -> Result<(), AppError> {
+    store.set_auto_archive_days(days)
 }
 
 #[tauri::command]
-fn move_todo_item(app_handle: tauri::AppHandle, id: u32, target_parent_id: Option<u32>, target_position: i32) {
+fn move_todo_item(store: tauri::State<'_, db::TodoStore>, id: u32, target_parent_id: Option<u32>, target_position: i32) -> Result<(), AppError> {
     println!("[BACKEND] move_todo_item called: id={}, parent={:?}, pos={}", id, target_parent_id, target_position);
-    match db::move_todo(&app_handle, id, target_parent_id, target_position) {
+    let result = store.move_todo(id, target_parent_id, target_position);
+    match &result {
         Ok(_) => println!("[BACKEND] ✅ move_todo succeeded"),
         Err(e) => println!("[BACKEND] ❌ move_todo failed: {}", e),
     }
+    result
 }
 
 #[tauri::command]
@@ -75,35 +379,259 @@ fn log_message(msg: String) {
 }
 
 #[tauri::command]
-fn set_todo_count(app_handle: tauri::AppHandle, id: u32, count: Option<i32>) {
-    let _ = db::set_todo_count(&app_handle, id, count);
+fn set_todo_count(store: tauri::State<'_, db::TodoStore>, id: u32, count: Option<i32>) -> Result<(), AppError> {
+    store.set_todo_count(id, count)
+}
+
+#[tauri::command]
+fn set_todo_description(
+    store: tauri::State<'_, db::TodoStore>,
+    id: u32,
+    description: Option<String>,
+) -> Result<(), AppError> {
+    store.set_todo_description(id, description)
+}
+
+#[tauri::command]
+fn set_todo_dates(
+    store: tauri::State<'_, db::TodoStore>,
+    id: u32,
+    due_at: Option<String>,
+    scheduled_for: Option<String>,
+) -> Result<(), AppError> {
+    store.set_todo_dates(id, due_at, scheduled_for)
+}
+
+#[tauri::command]
+fn set_todo_recurrence(
+    store: tauri::State<'_, db::TodoStore>,
+    id: u32,
+    rule: Option<recurrence::RecurrenceRule>,
+) -> Result<(), AppError> {
+    store.set_todo_recurrence(id, rule)
+}
+
+#[tauri::command]
+fn set_todo_priority(store: tauri::State<'_, db::TodoStore>, id: u32, priority: db::Priority) -> Result<(), AppError> {
+    store.set_todo_priority(id, priority)
+}
+
+#[tauri::command]
+fn get_sort_mode(store: tauri::State<'_, db::TodoStore>) -> Result<db::SortMode, AppError> {
+    store.sort_mode()
+}
+
+#[tauri::command]
+fn set_sort_mode(store: tauri::State<'_, db::TodoStore>, mode: db::SortMode) -> Result<(), AppError> {
+    store.set_sort_mode(mode)
+}
+
+#[tauri::command]
+fn get_note_view(store: tauri::State<'_, db::TodoStore>) -> Result<db::NoteView, AppError> {
+    store.note_view()
+}
+
+#[tauri::command]
+fn set_note_view(store: tauri::State<'_, db::TodoStore>, view: db::NoteView) -> Result<(), AppError> {
+    store.set_note_view(view)
+}
+
+#[tauri::command]
+fn get_tab(store: tauri::State<'_, db::TodoStore>) -> Result<db::Tab, AppError> {
+    store.tab()
+}
+
+#[tauri::command]
+fn set_tab(store: tauri::State<'_, db::TodoStore>, tab: db::Tab) -> Result<(), AppError> {
+    store.set_tab(tab)
+}
+
+#[tauri::command]
+fn decrement_todo(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.decrement_todo(id)
+}
+
+#[tauri::command]
+fn reset_all_todos(store: tauri::State<'_, db::TodoStore>, list_id: u32) -> Result<(), AppError> {
+    store.reset_all_todos(list_id)
+}
+
+#[tauri::command]
+fn load_reset_schedules(store: tauri::State<'_, db::TodoStore>, list_id: u32) -> Result<Vec<db::ResetSchedule>, AppError> {
+    store.get_reset_schedules(list_id)
+}
+
+#[tauri::command]
+fn add_reset_schedule(
+    store: tauri::State<'_, db::TodoStore>,
+    list_id: u32,
+    todo_id: Option<u32>,
+    frequency: schedule::ResetFrequency,
+    time: String,
+) -> Result<u32, AppError> {
+    store.add_reset_schedule(list_id, todo_id, frequency, time)
+}
+
+#[tauri::command]
+fn delete_reset_schedule(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.delete_reset_schedule(id)
+}
+
+/// Runs due reset schedules and auto-archiving every
+/// `schedule::CHECK_INTERVAL` and tells the UI to reload when either did
+/// something.
+fn spawn_scheduler(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(schedule::CHECK_INTERVAL);
+        let Some(store) = app.try_state::<db::TodoStore>() else {
+            return;
+        };
+        if run_scheduled_jobs(&store) {
+            let _ = app.emit("todos-changed", ());
+        }
+    });
+}
+
+/// Returns whether any todos changed.
+fn run_scheduled_jobs(store: &db::TodoStore) -> bool {
+    let resets = store.run_due_resets().unwrap_or_else(|e| {
+        println!("[DB] ❌ Scheduled reset failed: {}", e);
+        0
+    });
+    let archived = store.run_auto_archive().unwrap_or_else(|e| {
+        println!("[DB] ❌ Auto-archive failed: {}", e);
+        0
+    });
+    resets + archived > 0
+}
+
+#[tauri::command]
+fn load_lists(store: tauri::State<'_, db::TodoStore>) -> Result<Vec<db::List>, AppError> {
+    store.get_lists()
+}
+
+#[tauri::command]
+fn create_list(store: tauri::State<'_, db::TodoStore>, name: String) -> Result<u32, AppError> {
+    store.create_list(name)
+}
+
+#[tauri::command]
+fn rename_list(store: tauri::State<'_, db::TodoStore>, id: u32, name: String) -> Result<(), AppError> {
+    store.rename_list(id, name)
+}
+
+#[tauri::command]
+fn move_list(store: tauri::State<'_, db::TodoStore>, id: u32, position: i32) -> Result<(), AppError> {
+    store.move_list(id, position)
+}
+
+#[tauri::command]
+fn delete_list(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.delete_list(id)
+}
+
+#[tauri::command]
+fn get_active_list(store: tauri::State<'_, db::TodoStore>) -> Result<u32, AppError> {
+    store.active_list()
+}
+
+#[tauri::command]
+fn set_active_list(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.set_active_list(id)
+}
+
+#[tauri::command]
+fn load_todo_events(
+    store: tauri::State<'_, db::TodoStore>,
+    todo_id: Option<u32>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<db::TodoEvent>, AppError> {
+    store.get_todo_events(todo_id, since, until)
+}
+
+#[tauri::command]
+fn load_stats(store: tauri::State<'_, db::TodoStore>, days: u32) -> Result<stats::Stats, AppError> {
+    store.get_stats(days)
+}
+
+#[tauri::command]
+fn search(
+    store: tauri::State<'_, db::TodoStore>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<search::SearchHit>, AppError> {
+    store.search(&query, limit.unwrap_or(search::DEFAULT_LIMIT))
+}
+
+#[tauri::command]
+fn run_query(store: tauri::State<'_, db::TodoStore>, query: String) -> Result<db::QueryResult, AppError> {
+    store.run_query(&query)
+}
+
+#[tauri::command]
+fn load_views(store: tauri::State<'_, db::TodoStore>) -> Result<Vec<db::SavedView>, AppError> {
+    store.get_views()
+}
+
+#[tauri::command]
+fn create_view(
+    store: tauri::State<'_, db::TodoStore>,
+    name: String,
+    query: String,
+    layout: query::ViewLayout,
+) -> Result<u32, AppError> {
+    store.create_view(name, query, layout)
+}
+
+#[tauri::command]
+fn update_view(
+    store: tauri::State<'_, db::TodoStore>,
+    id: u32,
+    name: String,
+    query: String,
+    layout: query::ViewLayout,
+) -> Result<(), AppError> {
+    store.update_view(id, name, query, layout)
 }
 
 #[tauri::command]
-fn decrement_todo(app_handle: tauri::AppHandle, id: u32) {
-    let _ = db::decrement_todo(&app_handle, id);
+fn delete_view(store: tauri::State<'_, db::TodoStore>, id: u32) -> Result<(), AppError> {
+    store.delete_view(id)
 }
 
 #[tauri::command]
-fn reset_all_todos(app_handle: tauri::AppHandle) {
-    let _ = db::reset_all_todos(&app_handle);
+fn undo(store: tauri::State<'_, db::TodoStore>) -> Result<Option<String>, AppError> {
+    store.undo()
+}
+
+#[tauri::command]
+fn redo(store: tauri::State<'_, db::TodoStore>) -> Result<Option<String>, AppError> {
+    store.redo()
 }
 
 #[tauri::command]
 fn save_window_state(
-    app_handle: tauri::AppHandle,
+    window: tauri::Window,
+    store: tauri::State<'_, db::TodoStore>,
     width: f64,
     height: f64,
     x: f64,
     y: f64,
     pinned: bool,
-) {
-    let _ = db::save_window_state(&app_handle, width, height, x, y, pinned);
+) -> Result<(), AppError> {
+    match window_note_id(window.label()) {
+        Some(id) => store.save_note_window_state(id, width, height, x, y, pinned),
+        None => store.save_window_state(width, height, x, y, pinned),
+    }
 }
 
 #[tauri::command]
-fn load_window_state(app_handle: tauri::AppHandle) -> Option<db::WindowState> {
-    db::load_window_state(&app_handle).ok().flatten()
+fn load_window_state(window: tauri::Window, store: tauri::State<'_, db::TodoStore>) -> Result<Option<db::WindowState>, AppError> {
+    match window_note_id(window.label()) {
+        Some(id) => store.load_note_window_state(id),
+        None => store.load_window_state(),
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -113,6 +641,7 @@ pub fn run() {
             let _ = app.get_webview_window("main").expect("no main window").set_focus();
         }))
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) = event {
                 let win = window.clone();
@@ -139,37 +668,81 @@ pub fn run() {
                             
                             // For now, let's try to get it.
                             // If not, we'll read from DB first.
-                            let app_handle = win.app_handle();
-                            let pinned = if let Ok(Some(state)) = db::load_window_state(app_handle) {
+                            let Some(store) = win.try_state::<db::TodoStore>() else {
+                                return;
+                            };
+                            let note_id = window_note_id(win.label());
+                            let saved = match note_id {
+                                Some(id) => store.load_note_window_state(id),
+                                None => store.load_window_state(),
+                            };
+                            let pinned = if let Ok(Some(state)) = saved {
                                 state.pinned
                             } else {
                                 false
                             };
 
-                            let _ = db::save_window_state(
-                                app_handle,
-                                logical_size.width,
-                                logical_size.height,
-                                logical_pos.x,
-                                logical_pos.y,
-                                pinned
-                            );
+                            let _ = match note_id {
+                                Some(id) => store.save_note_window_state(
+                                    id,
+                                    logical_size.width,
+                                    logical_size.height,
+                                    logical_pos.x,
+                                    logical_pos.y,
+                                    pinned,
+                                ),
+                                None => store.save_window_state(
+                                    logical_size.width,
+                                    logical_size.height,
+                                    logical_pos.x,
+                                    logical_pos.y,
+                                    pinned
+                                ),
+                            };
                         }
                     }
                 });
             }
         })
         .setup(|app| {
-            db::init_db(app.handle())?;
+            let app_dir = app.path().app_data_dir()?;
+            std::fs::create_dir_all(&app_dir)?;
+            let db_path = app_dir.join("sticky_notes.db");
+            println!("Database path: {:?}", db_path);
+
+            match db::TodoStore::open(&db_path) {
+                Ok(store) => {
+                    // Catch up on work that fell due while the app was closed
+                    run_scheduled_jobs(&store);
+                    app.manage(store);
+                    app.manage(StartupError(None));
+                    spawn_scheduler(app.handle().clone());
+                }
+                Err(e) => {
+                    println!("[DB] ❌ Failed to open database: {}", e);
+                    app.manage(StartupError(Some(e)));
+                }
+            }
             
             // Restore window state
-            if let Some(window) = app.get_webview_window("main") {
-                 if let Ok(Some(state)) = db::load_window_state(app.handle()) {
+            if let (Some(window), Some(store)) = (app.get_webview_window("main"), app.try_state::<db::TodoStore>()) {
+                 if let Ok(Some(state)) = store.load_window_state() {
                      let _ = window.set_size(tauri::Size::Logical(tauri::LogicalSize { width: state.width, height: state.height }));
                      let _ = window.set_position(tauri::Position::Logical(tauri::LogicalPosition { x: state.x, y: state.y }));
                      let _ = window.set_always_on_top(state.pinned);
                  }
             }
+
+            // Reopen the notes that had a window of their own
+            if let Some(store) = app.try_state::<db::TodoStore>() {
+                let windows = store.note_windows().unwrap_or_default();
+                for (id, state) in windows {
+                    let title = store.get_note(id).map(|n| n.title).unwrap_or_default();
+                    if let Err(e) = build_note_window(app.handle(), id, &title, &state) {
+                        println!("[Notes] Failed to reopen window of note {}: {}", id, e);
+                    }
+                }
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -177,20 +750,82 @@ pub fn run() {
             set_always_on_top, 
             close_window, 
             start_drag,
+            load_notes,
             load_note,
             save_note_content,
+            load_note_revisions,
+            diff_note_revision,
+            restore_note_revision,
+            create_note,
+            rename_note,
+            move_note,
+            delete_note,
+            get_active_note,
+            set_active_note,
+            open_note_window,
+            window_note,
             load_todos,
+            export_markdown,
+            export_markdown_file,
+            import_markdown,
+            export_backup,
+            restore_backup,
+            restart_app,
+            load_tags,
             add_todo_item,
             update_todo_status,
             update_todo_text,
             remove_todo_item,
+            load_trash,
+            restore_todo_item,
+            purge_todo_item,
+            empty_trash,
+            archive_todo_item,
+            load_archive,
+            restore_archived_item,
+            purge_archived_item,
+            get_auto_archive_days,
+            set_auto_archive_days,
+            get_trash_retention_days,
+            set_trash_retention_days,
             move_todo_item,
             log_message,
             set_todo_count,
+            set_todo_dates,
+            set_todo_description,
+            set_todo_recurrence,
+            set_todo_priority,
+            get_sort_mode,
+            set_sort_mode,
+            get_note_view,
+            set_note_view,
+            get_tab,
+            set_tab,
             decrement_todo,
             reset_all_todos,
+            load_reset_schedules,
+            add_reset_schedule,
+            delete_reset_schedule,
+            load_lists,
+            create_list,
+            rename_list,
+            move_list,
+            delete_list,
+            get_active_list,
+            set_active_list,
+            load_todo_events,
+            load_stats,
+            search,
+            run_query,
+            load_views,
+            create_view,
+            update_view,
+            delete_view,
+            undo,
+            redo,
             save_window_state,
-            load_window_state
+            load_window_state,
+            startup_error
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,58 @@
+//! Inline markers typed into todo text, e.g. `Call Bob !high`.
+//!
+//! Markers are whole whitespace-separated words. Priority markers are
+//! stripped from the stored text and applied to the item's fields instead,
+//! `#tags` stay in the text and are mirrored into the `todo_tags` table.
+
+use crate::db::Priority;
+
+/// Removes priority markers from `text`. Returns the cleaned text and the
+/// last priority found, if any. Text that consists of nothing but markers is
+/// left alone so an item never ends up empty.
+pub fn extract_priority(text: &str) -> (String, Option<Priority>) {
+    let mut priority = None;
+    let mut kept = Vec::new();
+
+    for word in text.split(' ') {
+        match word.strip_prefix('!').and_then(Priority::from_marker) {
+            Some(p) => priority = Some(p),
+            None => kept.push(word),
+        }
+    }
+
+    let cleaned = kept
+        .into_iter()
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if priority.is_none() || cleaned.is_empty() {
+        return (text.to_string(), None);
+    }
+    (cleaned, priority)
+}
+
+/// Returns the `#tag` names in `text`, without the `#`, deduplicated
+/// case-insensitively in order of appearance. A tag runs until the first
+/// character that is not alphanumeric, `_`, `-` or `/`, so `#work,` is
+/// `work`. Purely numeric tokens like `#12` are not tags.
+pub fn extract_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for word in text.split_whitespace() {
+        let Some(rest) = word.strip_prefix('#') else {
+            continue;
+        };
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+            .unwrap_or(rest.len());
+        let name = &rest[..end];
+        if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(name)) {
+            tags.push(name.to_string());
+        }
+    }
+
+    tags
+}
@@ -0,0 +1,640 @@
+use rusqlite::{params, Connection, Transaction};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A single schema change. `version` is written to `PRAGMA user_version`
+/// once `up` has run, so every migration is applied exactly once.
+pub struct Migration {
+    pub version: i32,
+    pub description: &'static str,
+    pub up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+// Append new migrations at the end, never edit or reorder existing ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "undo history journal",
+        up: history_journal,
+    },
+    Migration {
+        version: 3,
+        description: "trash and settings",
+        up: trash_and_settings,
+    },
+    Migration {
+        version: 4,
+        description: "due and scheduled dates",
+        up: todo_dates,
+    },
+    Migration {
+        version: 5,
+        description: "todo priority",
+        up: todo_priority,
+    },
+    Migration {
+        version: 6,
+        description: "tags",
+        up: tags,
+    },
+    Migration {
+        version: 7,
+        description: "named lists",
+        up: named_lists,
+    },
+    Migration {
+        version: 8,
+        description: "recurring todos",
+        up: todo_recurrence,
+    },
+    Migration {
+        version: 9,
+        description: "reset schedules",
+        up: reset_schedules,
+    },
+    Migration {
+        version: 10,
+        description: "todo event log",
+        up: todo_events,
+    },
+    Migration {
+        version: 11,
+        description: "full-text search",
+        up: full_text_search,
+    },
+    Migration {
+        version: 12,
+        description: "saved views",
+        up: saved_views,
+    },
+    Migration {
+        version: 13,
+        description: "archive",
+        up: archive,
+    },
+    Migration {
+        version: 14,
+        description: "todo descriptions",
+        up: todo_descriptions,
+    },
+    Migration {
+        version: 15,
+        description: "multiple notes",
+        up: multiple_notes,
+    },
+    Migration {
+        version: 16,
+        description: "note revisions",
+        up: note_revisions,
+    },
+];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The database was written by a newer build of the app.
+    TooNew { found: i32, supported: i32 },
+    /// Copying the database aside before migrating failed, nothing was changed.
+    Backup(rusqlite::Error),
+    /// A migration failed and was rolled back.
+    Failed {
+        version: i32,
+        description: &'static str,
+        backup: Option<PathBuf>,
+        source: rusqlite::Error,
+    },
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::TooNew { found, supported } => write!(
+                f,
+                "database schema version {} is newer than this app supports ({})",
+                found, supported
+            ),
+            MigrationError::Backup(e) => write!(f, "could not back up database before upgrading: {}", e),
+            MigrationError::Failed { version, description, backup, source } => {
+                write!(f, "database upgrade {} ({}) failed: {}", version, description, source)?;
+                if let Some(path) = backup {
+                    write!(f, ". A backup was saved to {}", path.display())?;
+                }
+                Ok(())
+            }
+            MigrationError::Sqlite(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrationError::TooNew { .. } => None,
+            MigrationError::Backup(e) | MigrationError::Sqlite(e) => Some(e),
+            MigrationError::Failed { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Brings the schema up to `latest_version()`.
+///
+/// If `db_path` is given and the database already holds data, a copy is
+/// written next to it before the first pending migration runs.
+pub fn migrate(conn: &mut Connection, db_path: Option<&Path>) -> Result<(), MigrationError> {
+    let current: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(MigrationError::TooNew { found: current, supported: latest });
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    let backup = match db_path {
+        Some(path) if has_user_tables(conn)? => Some(backup_database(conn, path, current)?),
+        _ => None,
+    };
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        println!("[DB] Applying migration {}: {}", migration.version, migration.description);
+        apply(conn, migration).map_err(|source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            backup: backup.clone(),
+            source,
+        })?;
+    }
+
+    Ok(())
+}
+
+fn apply(conn: &mut Connection, migration: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    (migration.up)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+fn has_user_tables(conn: &Connection) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%')",
+        [],
+        |row| row.get(0),
+    )
+}
+
+// VACUUM INTO produces a consistent copy even while other connections are open.
+fn backup_database(conn: &Connection, db_path: &Path, version: i32) -> Result<PathBuf, MigrationError> {
+    let file_name = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "database".to_string());
+    let backup_path = db_path.with_file_name(format!("{}.v{}.bak", file_name, version));
+
+    if backup_path.exists() {
+        let _ = std::fs::remove_file(&backup_path);
+    }
+    conn.execute("VACUUM INTO ?1", params![backup_path.to_string_lossy()])
+        .map_err(MigrationError::Backup)?;
+
+    println!("[DB] Backed up database to {:?}", backup_path);
+    Ok(backup_path)
+}
+
+fn has_column(tx: &Transaction, table: &str, column: &str) -> rusqlite::Result<bool> {
+    tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+        params![table, column],
+        |row| row.get(0),
+    )
+}
+
+fn add_column_if_missing(tx: &Transaction, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    if !has_column(tx, table, column)? {
+        tx.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+// Matches the schema that unversioned builds created, including the columns
+// they bolted on with ALTER TABLE, so it is safe on both new and old files.
+fn initial_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS notes (
+            id INTEGER PRIMARY KEY,
+            content TEXT
+        )",
+        [],
+    )?;
+
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS todos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            text TEXT NOT NULL,
+            completed BOOLEAN NOT NULL,
+            parent_id INTEGER,
+            position INTEGER DEFAULT 0,
+            target_count INTEGER,
+            current_count INTEGER DEFAULT 0,
+            FOREIGN KEY(parent_id) REFERENCES todos(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    add_column_if_missing(tx, "todos", "parent_id", "INTEGER")?;
+    add_column_if_missing(tx, "todos", "position", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(tx, "todos", "target_count", "INTEGER")?;
+    add_column_if_missing(tx, "todos", "current_count", "INTEGER DEFAULT 0")?;
+
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS window_state (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            width REAL NOT NULL DEFAULT 300,
+            height REAL NOT NULL DEFAULT 300,
+            x REAL NOT NULL DEFAULT 100,
+            y REAL NOT NULL DEFAULT 100,
+            pinned INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+
+    tx.execute("INSERT OR IGNORE INTO notes (id, content) VALUES (1, '')", [])?;
+
+    Ok(())
+}
+
+fn history_journal(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn trash_and_settings(tx: &Transaction) -> rusqlite::Result<()> {
+    // Unix time in milliseconds, NULL for live items
+    tx.execute("ALTER TABLE todos ADD COLUMN deleted_at INTEGER", [])?;
+    tx.execute("CREATE INDEX idx_todos_deleted_at ON todos(deleted_at)", [])?;
+
+    tx.execute(
+        "CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn todo_dates(tx: &Transaction) -> rusqlite::Result<()> {
+    // Plain `YYYY-MM-DD` strings, they sort and compare correctly as text
+    tx.execute("ALTER TABLE todos ADD COLUMN due_at TEXT", [])?;
+    tx.execute("ALTER TABLE todos ADD COLUMN scheduled_for TEXT", [])?;
+    Ok(())
+}
+
+fn todo_priority(tx: &Transaction) -> rusqlite::Result<()> {
+    // 0 = none up to 4 = urgent, see `db::Priority`
+    tx.execute("ALTER TABLE todos ADD COLUMN priority INTEGER NOT NULL DEFAULT 0", [])?;
+    Ok(())
+}
+
+fn tags(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        )",
+        [],
+    )?;
+    tx.execute(
+        "CREATE TABLE todo_tags (
+            todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (todo_id, tag_id)
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX idx_todo_tags_tag_id ON todo_tags(tag_id)", [])?;
+
+    // Tag the items that already have `#tags` in their text
+    let mut stmt = tx.prepare("SELECT id, text FROM todos")?;
+    let todos = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (id, text) in todos {
+        for name in crate::markers::extract_tags(&text) {
+            tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![name])?;
+            tx.execute(
+                "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+                params![id, name],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn named_lists(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE lists (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    tx.execute("INSERT INTO lists (id, name, position) VALUES (1, 'Todos', 0)", [])?;
+
+    // Every item carries its list, children included, so sibling queries
+    // only need (list_id, parent_id). No REFERENCES clause: SQLite refuses
+    // one with a non-NULL default when adding a column.
+    tx.execute("ALTER TABLE todos ADD COLUMN list_id INTEGER NOT NULL DEFAULT 1", [])?;
+    tx.execute("CREATE INDEX idx_todos_list_parent ON todos(list_id, parent_id)", [])?;
+    Ok(())
+}
+
+fn todo_recurrence(tx: &Transaction) -> rusqlite::Result<()> {
+    // JSON `recurrence::RecurrenceRule`, NULL for one-off items
+    tx.execute("ALTER TABLE todos ADD COLUMN recurrence TEXT", [])?;
+    Ok(())
+}
+
+fn reset_schedules(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE reset_schedules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            list_id INTEGER NOT NULL REFERENCES lists(id) ON DELETE CASCADE,
+            -- NULL resets the whole list
+            todo_id INTEGER REFERENCES todos(id) ON DELETE CASCADE,
+            -- NULL for daily, 0 = Monday .. 6 = Sunday for weekly
+            weekday INTEGER,
+            -- Local time, HH:MM
+            time TEXT NOT NULL,
+            -- Unix time in milliseconds
+            last_reset_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn todo_events(tx: &Transaction) -> rusqlite::Result<()> {
+    // No foreign key to todos: the log outlives the items it talks about
+    tx.execute(
+        "CREATE TABLE todo_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            -- NULL for events about a whole list
+            todo_id INTEGER,
+            list_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            -- Kind specific, e.g. the count left after a decrement
+            value INTEGER,
+            -- Unix time in milliseconds
+            created_at INTEGER NOT NULL,
+            -- The undo entry that wrote the event, hidden while it is undone
+            history_id INTEGER,
+            undone INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX idx_todo_events_todo ON todo_events(todo_id, created_at)", [])?;
+    tx.execute("CREATE INDEX idx_todo_events_created_at ON todo_events(created_at)", [])?;
+    tx.execute("CREATE INDEX idx_todo_events_history ON todo_events(history_id)", [])?;
+    Ok(())
+}
+
+fn full_text_search(tx: &Transaction) -> rusqlite::Result<()> {
+    // External content tables: the text lives in todos/notes only and the
+    // triggers below keep the indexes in step with every write, undo included
+    for (table, column) in [("todos", "text"), ("notes", "content")] {
+        tx.execute(
+            &format!(
+                "CREATE VIRTUAL TABLE {table}_fts USING fts5(
+                    {column}, content='{table}', content_rowid='id',
+                    tokenize='unicode61 remove_diacritics 2'
+                )"
+            ),
+            [],
+        )?;
+        tx.execute(
+            &format!(
+                "CREATE TRIGGER {table}_fts_insert AFTER INSERT ON {table} BEGIN
+                    INSERT INTO {table}_fts(rowid, {column}) VALUES (new.id, new.{column});
+                END"
+            ),
+            [],
+        )?;
+        tx.execute(
+            &format!(
+                "CREATE TRIGGER {table}_fts_delete AFTER DELETE ON {table} BEGIN
+                    INSERT INTO {table}_fts({table}_fts, rowid, {column}) VALUES ('delete', old.id, old.{column});
+                END"
+            ),
+            [],
+        )?;
+        tx.execute(
+            &format!(
+                "CREATE TRIGGER {table}_fts_update AFTER UPDATE OF {column} ON {table} BEGIN
+                    INSERT INTO {table}_fts({table}_fts, rowid, {column}) VALUES ('delete', old.id, old.{column});
+                    INSERT INTO {table}_fts(rowid, {column}) VALUES (new.id, new.{column});
+                END"
+            ),
+            [],
+        )?;
+        tx.execute(&format!("INSERT INTO {table}_fts({table}_fts) VALUES ('rebuild')"), [])?;
+    }
+    Ok(())
+}
+
+fn saved_views(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute(
+        "CREATE TABLE saved_views (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            -- Source text, parsed again on every run, see query.rs
+            query TEXT NOT NULL,
+            -- 'tree' or 'flat'
+            layout TEXT NOT NULL DEFAULT 'tree',
+            position INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn archive(tx: &Transaction) -> rusqlite::Result<()> {
+    // Unix time in milliseconds, NULL while the item is open
+    tx.execute("ALTER TABLE todos ADD COLUMN completed_at INTEGER", [])?;
+    // Unix time in milliseconds, NULL unless archived. Archived rows keep
+    // their parent and position so they can go back where they were.
+    tx.execute("ALTER TABLE todos ADD COLUMN archived_at INTEGER", [])?;
+
+    // Completion can change in many places (cascades, resets, recurrence),
+    // so the timestamp follows the flag in one trigger. An UPDATE that sets
+    // completed_at itself, like undo, wins over the trigger.
+    tx.execute(
+        "CREATE TRIGGER todos_completed_at AFTER UPDATE OF completed ON todos
+         WHEN new.completed IS NOT old.completed AND new.completed_at IS old.completed_at
+         BEGIN
+            UPDATE todos SET completed_at = CASE
+                WHEN new.completed THEN CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+            END
+            WHERE id = new.id;
+         END",
+        [],
+    )?;
+    // The real completion time is unknown, count from the upgrade
+    tx.execute(
+        "UPDATE todos SET completed_at = CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+         WHERE completed = 1",
+        [],
+    )?;
+    Ok(())
+}
+
+fn todo_descriptions(tx: &Transaction) -> rusqlite::Result<()> {
+    // Markdown, NULL when the item has none
+    tx.execute("ALTER TABLE todos ADD COLUMN description TEXT", [])?;
+    Ok(())
+}
+
+fn multiple_notes(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute("ALTER TABLE notes ADD COLUMN title TEXT NOT NULL DEFAULT ''", [])?;
+    tx.execute("ALTER TABLE notes ADD COLUMN position INTEGER NOT NULL DEFAULT 0", [])?;
+    // Unix time in milliseconds of the last content change
+    tx.execute("ALTER TABLE notes ADD COLUMN updated_at INTEGER", [])?;
+    tx.execute("UPDATE notes SET title = 'Note' WHERE title = ''", [])?;
+
+    // A note with a row here has its own sticky window, labelled
+    // `note-<id>`, which is reopened at startup
+    tx.execute(
+        "CREATE TABLE note_windows (
+            note_id INTEGER PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+            width REAL NOT NULL DEFAULT 300,
+            height REAL NOT NULL DEFAULT 300,
+            x REAL NOT NULL DEFAULT 100,
+            y REAL NOT NULL DEFAULT 100,
+            pinned INTEGER NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+
+    // Index titles as well, rebuilt from scratch like in full_text_search
+    for trigger in ["insert", "delete", "update"] {
+        tx.execute(&format!("DROP TRIGGER notes_fts_{trigger}"), [])?;
+    }
+    tx.execute("DROP TABLE notes_fts", [])?;
+    tx.execute(
+        "CREATE VIRTUAL TABLE notes_fts USING fts5(
+            title, content, content='notes', content_rowid='id',
+            tokenize='unicode61 remove_diacritics 2'
+        )",
+        [],
+    )?;
+    tx.execute(
+        "CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+            INSERT INTO notes_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+        END",
+        [],
+    )?;
+    tx.execute(
+        "CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+        END",
+        [],
+    )?;
+    tx.execute(
+        "CREATE TRIGGER notes_fts_update AFTER UPDATE OF title, content ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content) VALUES ('delete', old.id, old.title, old.content);
+            INSERT INTO notes_fts(rowid, title, content) VALUES (new.id, new.title, new.content);
+        END",
+        [],
+    )?;
+    tx.execute("INSERT INTO notes_fts(notes_fts) VALUES ('rebuild')", [])?;
+    Ok(())
+}
+
+fn note_revisions(tx: &Transaction) -> rusqlite::Result<()> {
+    // Earlier texts of a note, see revisions.rs for when they are taken
+    tx.execute(
+        "CREATE TABLE note_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            content TEXT NOT NULL,
+            -- Unix time in milliseconds
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    tx.execute("CREATE INDEX idx_note_revisions_note ON note_revisions(note_id, created_at)", [])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(conn: &Connection) -> i32 {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn migrates_a_fresh_database_once() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, None).unwrap();
+        assert_eq!(version(&conn), latest_version());
+        migrate(&mut conn, None).unwrap();
+        assert_eq!(version(&conn), latest_version());
+    }
+
+    #[test]
+    fn upgrades_an_unversioned_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, content TEXT);
+             INSERT INTO notes VALUES (1, 'Hello');
+             CREATE TABLE todos (id INTEGER PRIMARY KEY AUTOINCREMENT, text TEXT NOT NULL, completed BOOLEAN NOT NULL);
+             INSERT INTO todos (text, completed) VALUES ('Old item', 1);",
+        )
+        .unwrap();
+        migrate(&mut conn, None).unwrap();
+
+        let content: String = conn.query_row("SELECT content FROM notes WHERE id = 1", [], |row| row.get(0)).unwrap();
+        assert_eq!(content, "Hello");
+        let (list_id, completed): (u32, bool) = conn
+            .query_row("SELECT list_id, completed FROM todos WHERE text = 'Old item'", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((list_id, completed), (1, true));
+    }
+
+    #[test]
+    fn refuses_a_newer_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+        assert!(matches!(migrate(&mut conn, None), Err(MigrationError::TooNew { .. })));
+    }
+}
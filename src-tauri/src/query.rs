@@ -0,0 +1,374 @@
+//! The query language behind saved views, e.g. `tag:work !done due<7d has:count`.
+//!
+//! A query is a list of whitespace-separated terms that must all match. A
+//! leading `!` or `-` negates a term. Terms:
+//!
+//! - `tag:NAME` - tagged `#NAME`
+//! - `done` - completed
+//! - `has:count|due|scheduled|repeat|tags|children`
+//! - `due` / `scheduled` followed by `<`, `<=`, `>`, `>=` or `:` and a date:
+//!   `YYYY-MM-DD`, `today`, or days (`7d`) or weeks (`2w`) from today
+//! - `priority` / `p` followed by `:`, `<`, `<=`, `>` or `>=` and a
+//!   priority (`none`, `low`, `med`, `high`, `urgent`)
+//! - `list:NAME` - in the list called NAME
+//! - anything else, or a `"quoted phrase"`, is looked for in the text
+//!
+//! Relative dates are resolved when the query runs, so a saved `due<7d`
+//! always means the coming week.
+
+use crate::db::{Priority, DATE_FORMAT};
+use crate::error::{AppError, Result};
+use chrono::{Duration, NaiveDate};
+use rusqlite::types::{FromSql, FromSqlResult, Value, ValueRef};
+use serde::{Deserialize, Serialize};
+
+/// How a saved view shows its results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewLayout {
+    /// Matches in their tree, with their ancestors for context.
+    #[default]
+    Tree,
+    /// Matches only, one after the other.
+    Flat,
+}
+
+impl ViewLayout {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ViewLayout::Tree => "tree",
+            ViewLayout::Flat => "flat",
+        }
+    }
+}
+
+impl FromSql for ViewLayout {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Ok(match value.as_str()? {
+            "flat" => ViewLayout::Flat,
+            _ => ViewLayout::Tree,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Count,
+    Due,
+    Scheduled,
+    Repeat,
+    Tags,
+    Children,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Cmp {
+    fn sql(self) -> &'static str {
+        match self {
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Eq => "=",
+            Cmp::Ge => ">=",
+            Cmp::Gt => ">",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DateValue {
+    Absolute(NaiveDate),
+    /// Days from today.
+    Relative(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Tag(String),
+    Done,
+    Has(Field),
+    Date { field: Field, cmp: Cmp, value: DateValue },
+    Priority { cmp: Cmp, priority: Priority },
+    List(String),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self> {
+        let terms = split_terms(input)?
+            .into_iter()
+            .map(|(word, quoted)| parse_term(&word, quoted))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Query { terms })
+    }
+
+    /// A condition on the live todo aliased `t`, with its parameters. An
+    /// empty query matches everything.
+    pub fn to_sql(&self, today: NaiveDate) -> (String, Vec<Value>) {
+        let mut params = Vec::new();
+        let mut conditions = vec!["t.deleted_at IS NULL AND t.archived_at IS NULL".to_string()];
+
+        for term in &self.terms {
+            let condition = filter_sql(&term.filter, today, &mut params);
+            conditions.push(if term.negated { format!("NOT ({})", condition) } else { condition });
+        }
+
+        (conditions.join(" AND "), params)
+    }
+}
+
+/// Splits on whitespace, keeping `"quoted phrases"` (also after a prefix,
+/// as in `list:"Side projects"`) together. The flag tells whether the term
+/// itself was quoted, which makes it plain text.
+fn split_terms(input: &str) -> Result<Vec<(String, bool)>> {
+    let mut terms = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut raw = String::new();
+        let mut word = String::new();
+        let mut in_quotes = false;
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() && !in_quotes {
+                break;
+            }
+            if c == '"' {
+                in_quotes = !in_quotes;
+            } else {
+                word.push(c);
+            }
+            raw.push(c);
+            chars.next();
+        }
+        if in_quotes {
+            return Err(AppError::InvalidInput(format!("Unclosed quote in \"{}\"", input)));
+        }
+        let quoted = raw.trim_start_matches(['!', '-']).starts_with('"');
+        terms.push((word, quoted));
+    }
+
+    Ok(terms)
+}
+
+fn parse_term(word: &str, quoted: bool) -> Result<Term> {
+    let (negated, body) = match word.strip_prefix('!').or_else(|| word.strip_prefix('-')) {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, word),
+    };
+    if quoted {
+        return Ok(Term { negated, filter: Filter::Text(body.to_string()) });
+    }
+
+    let invalid = |reason: &str| AppError::InvalidInput(format!("{} in \"{}\"", reason, word));
+
+    let filter = if body.eq_ignore_ascii_case("done") {
+        Filter::Done
+    } else if let Some(name) = strip_prefix_ci(body, "tag:") {
+        let name = name.trim_start_matches('#');
+        if name.is_empty() {
+            return Err(invalid("Missing tag name"));
+        }
+        Filter::Tag(name.to_string())
+    } else if let Some(name) = strip_prefix_ci(body, "list:") {
+        if name.is_empty() {
+            return Err(invalid("Missing list name"));
+        }
+        Filter::List(name.to_string())
+    } else if let Some(field) = strip_prefix_ci(body, "has:") {
+        Filter::Has(match field.to_ascii_lowercase().as_str() {
+            "count" => Field::Count,
+            "due" => Field::Due,
+            "scheduled" => Field::Scheduled,
+            "repeat" => Field::Repeat,
+            "tags" | "tag" => Field::Tags,
+            "children" | "subtasks" => Field::Children,
+            _ => return Err(invalid("Unknown field")),
+        })
+    } else if let Some((field, cmp, value)) = split_comparison(body, &["due", "scheduled"]) {
+        let field = if field == "due" { Field::Due } else { Field::Scheduled };
+        let value = parse_date_value(value).ok_or_else(|| invalid("Invalid date"))?;
+        Filter::Date { field, cmp, value }
+    } else if let Some((_, cmp, value)) = split_comparison(body, &["priority", "p"]) {
+        let priority = match value.to_ascii_lowercase().as_str() {
+            "none" => Priority::None,
+            other => Priority::from_marker(other).ok_or_else(|| invalid("Unknown priority"))?,
+        };
+        Filter::Priority { cmp, priority }
+    } else {
+        Filter::Text(body.to_string())
+    };
+
+    Ok(Term { negated, filter })
+}
+
+fn strip_prefix_ci<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    value
+        .get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &value[prefix.len()..])
+}
+
+/// Splits `name<op>value` for one of `names`, e.g. `due<=7d`.
+fn split_comparison<'a>(term: &'a str, names: &[&'static str]) -> Option<(&'static str, Cmp, &'a str)> {
+    const OPERATORS: [(&str, Cmp); 6] = [
+        ("<=", Cmp::Le),
+        (">=", Cmp::Ge),
+        ("<", Cmp::Lt),
+        (">", Cmp::Gt),
+        ("=", Cmp::Eq),
+        (":", Cmp::Eq),
+    ];
+    names.iter().find_map(|name| {
+        let rest = strip_prefix_ci(term, name)?;
+        OPERATORS
+            .iter()
+            .find_map(|(op, cmp)| rest.strip_prefix(op).map(|value| (*name, *cmp, value)))
+    })
+}
+
+/// Relative dates further out than this are rejected rather than left to
+/// overflow when the query runs.
+const MAX_RELATIVE_DAYS: i64 = 100 * 366;
+
+fn parse_date_value(value: &str) -> Option<DateValue> {
+    let value = value.to_ascii_lowercase();
+    if value == "today" {
+        return Some(DateValue::Relative(0));
+    }
+    let days = if let Some(days) = value.strip_suffix('d') {
+        days.parse::<i64>().ok()
+    } else if let Some(weeks) = value.strip_suffix('w') {
+        weeks.parse::<i64>().ok().and_then(|w| w.checked_mul(7))
+    } else {
+        return NaiveDate::parse_from_str(&value, DATE_FORMAT).ok().map(DateValue::Absolute);
+    };
+    days.filter(|d| d.abs() <= MAX_RELATIVE_DAYS).map(DateValue::Relative)
+}
+
+fn filter_sql(filter: &Filter, today: NaiveDate, params: &mut Vec<Value>) -> String {
+    let mut bind = |value: Value| {
+        params.push(value);
+        format!("?{}", params.len())
+    };
+
+    match filter {
+        Filter::Done => "t.completed = 1".to_string(),
+        Filter::Tag(name) => format!(
+            "EXISTS (SELECT 1 FROM todo_tags tt JOIN tags g ON g.id = tt.tag_id
+                     WHERE tt.todo_id = t.id AND g.name = {} COLLATE NOCASE)",
+            bind(Value::Text(name.clone()))
+        ),
+        Filter::List(name) => format!(
+            "t.list_id IN (SELECT id FROM lists WHERE name = {} COLLATE NOCASE)",
+            bind(Value::Text(name.clone()))
+        ),
+        Filter::Has(field) => match field {
+            Field::Count => "t.target_count IS NOT NULL".to_string(),
+            Field::Due => "t.due_at IS NOT NULL".to_string(),
+            Field::Scheduled => "t.scheduled_for IS NOT NULL".to_string(),
+            Field::Repeat => "t.recurrence IS NOT NULL".to_string(),
+            Field::Tags => "EXISTS (SELECT 1 FROM todo_tags tt WHERE tt.todo_id = t.id)".to_string(),
+            Field::Children => {
+                "EXISTS (SELECT 1 FROM todos c WHERE c.parent_id = t.id AND c.deleted_at IS NULL AND c.archived_at IS NULL)".to_string()
+            }
+        },
+        Filter::Date { field, cmp, value } => {
+            let column = if *field == Field::Due { "t.due_at" } else { "t.scheduled_for" };
+            let date = match value {
+                DateValue::Absolute(date) => *date,
+                // Parsing caps the offset, the fallback only guards odd `today`s
+                DateValue::Relative(days) => today
+                    .checked_add_signed(Duration::days(*days))
+                    .unwrap_or(if *days < 0 { NaiveDate::MIN } else { NaiveDate::MAX }),
+            };
+            // Dates are stored as YYYY-MM-DD, which compares like text
+            format!(
+                "{column} IS NOT NULL AND {column} {} {}",
+                cmp.sql(),
+                bind(Value::Text(date.format(DATE_FORMAT).to_string()))
+            )
+        }
+        Filter::Priority { cmp, priority } => {
+            format!("t.priority {} {}", cmp.sql(), bind(Value::Integer(*priority as i64)))
+        }
+        Filter::Text(text) => {
+            let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            format!("t.text LIKE {} ESCAPE '\\'", bind(Value::Text(format!("%{}%", escaped))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()
+    }
+
+    #[test]
+    fn parses_relative_and_absolute_dates() {
+        let query = Query::parse("due<=7d scheduled>2w due:today due=2024-04-01").unwrap();
+        let (_, params) = query.to_sql(today());
+        let dates: Vec<_> = params
+            .into_iter()
+            .map(|p| match p {
+                Value::Text(text) => text,
+                other => panic!("unexpected parameter {:?}", other),
+            })
+            .collect();
+        assert_eq!(dates, ["2024-03-22", "2024-03-29", "2024-03-15", "2024-04-01"]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_dates() {
+        for input in [
+            "due<99999999999d",
+            "due>-99999999999d",
+            "due<9223372036854775807w",
+            "scheduled<9999999w",
+            "due<36601d",
+        ] {
+            assert!(matches!(Query::parse(input), Err(AppError::InvalidInput(_))), "{}", input);
+        }
+        let query = Query::parse("due<36600d due>-5228w").unwrap();
+        query.to_sql(today());
+    }
+
+    #[test]
+    fn parses_other_terms() {
+        let query = Query::parse(r#"!done tag:#work list:"Side projects" p>=high has:due "due<3d""#).unwrap();
+        let (sql, params) = query.to_sql(today());
+        assert!(sql.contains("NOT (t.completed = 1)"));
+        assert_eq!(params.len(), 4);
+        assert_eq!(params[3], Value::Text("%due<3d%".to_string()));
+
+        assert!(Query::parse("has:nothing").is_err());
+        assert!(Query::parse("p:extreme").is_err());
+        assert!(Query::parse("tag:").is_err());
+        assert!(Query::parse("\"unclosed").is_err());
+        assert_eq!(Query::parse("").unwrap().to_sql(today()).1, Vec::<Value>::new());
+    }
+}
@@ -0,0 +1,152 @@
+//! Recurrence rules for repeating todos.
+//!
+//! A rule is stored as JSON in `todos.recurrence`. When a recurring item is
+//! completed, `next_date` picks its next due date and the store either
+//! resets the item in place or leaves it done and adds a fresh copy.
+
+use crate::error::{AppError, Result};
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Repeat {
+    Daily,
+    /// Days of the week, 0 = Monday .. 6 = Sunday.
+    Weekly { weekdays: Vec<u8> },
+    /// Day of the month, clamped to the last day in shorter months.
+    Monthly { day: u8 },
+    /// Counted from the day the item was completed, not from its due date.
+    AfterCompletion { days: u32 },
+}
+
+/// What completing a recurring item does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceMode {
+    /// Uncheck the item and its subtree and move its dates forward.
+    #[default]
+    Reschedule,
+    /// Keep the completed item and add a copy of it, subtree included, for
+    /// the next occurrence.
+    Spawn,
+}
+
+/// Longest gap an `AfterCompletion` rule may ask for.
+pub const MAX_INTERVAL_DAYS: u32 = 10 * 366;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    pub repeat: Repeat,
+    #[serde(default)]
+    pub mode: RecurrenceMode,
+}
+
+impl RecurrenceRule {
+    pub fn validate(&self) -> Result<()> {
+        let valid = match &self.repeat {
+            Repeat::Daily => true,
+            Repeat::Weekly { weekdays } => (1..=7).contains(&weekdays.len()) && weekdays.iter().all(|d| *d < 7),
+            Repeat::Monthly { day } => (1..=31).contains(day),
+            Repeat::AfterCompletion { days } => (1..=MAX_INTERVAL_DAYS).contains(days),
+        };
+        if !valid {
+            return Err(AppError::InvalidInput(format!("Invalid recurrence rule {:?}", self.repeat)));
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parses a stored rule. A rule this build cannot read counts as none.
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+}
+
+/// The next due date after an item due on `due` (if it has one) was
+/// completed on `today`. Fixed schedules never land on or before today, so a
+/// late completion skips the occurrences that were missed. Fails when the
+/// next occurrence is past the last date chrono can represent.
+pub fn next_date(repeat: &Repeat, due: Option<NaiveDate>, today: NaiveDate) -> Result<NaiveDate> {
+    let after = due.map_or(today, |d| d.max(today));
+    let next = match repeat {
+        Repeat::Daily => after.checked_add_days(Days::new(1)),
+        Repeat::Weekly { weekdays } => (1..=7)
+            .filter_map(|n| after.checked_add_days(Days::new(n)))
+            .find(|d| weekdays.contains(&(d.weekday().num_days_from_monday() as u8))),
+        Repeat::Monthly { day } => next_monthly(after, *day),
+        Repeat::AfterCompletion { days } => today.checked_add_days(Days::new(u64::from(*days))),
+    };
+    next.ok_or_else(|| AppError::InvalidInput(format!("No occurrence of {:?} after {}", repeat, after)))
+}
+
+fn next_monthly(after: NaiveDate, day: u8) -> Option<NaiveDate> {
+    let this_month = day_in_month(after.year(), after.month(), day)?;
+    if this_month > after {
+        Some(this_month)
+    } else if after.month() == 12 {
+        day_in_month(after.year().checked_add(1)?, 1, day)
+    } else {
+        day_in_month(after.year(), after.month() + 1, day)
+    }
+}
+
+fn day_in_month(year: i32, month: u32, day: u8) -> Option<NaiveDate> {
+    (1..=u32::from(day)).rev().find_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rule(repeat: Repeat) -> RecurrenceRule {
+        RecurrenceRule { repeat, mode: RecurrenceMode::default() }
+    }
+
+    #[test]
+    fn validates_intervals() {
+        assert!(rule(Repeat::AfterCompletion { days: 1 }).validate().is_ok());
+        assert!(rule(Repeat::AfterCompletion { days: MAX_INTERVAL_DAYS }).validate().is_ok());
+        assert!(rule(Repeat::AfterCompletion { days: 0 }).validate().is_err());
+        assert!(rule(Repeat::AfterCompletion { days: 200_000_000 }).validate().is_err());
+        assert!(rule(Repeat::Weekly { weekdays: vec![] }).validate().is_err());
+        assert!(rule(Repeat::Weekly { weekdays: vec![7] }).validate().is_err());
+        assert!(rule(Repeat::Weekly { weekdays: vec![0; 8] }).validate().is_err());
+        assert!(rule(Repeat::Monthly { day: 0 }).validate().is_err());
+        assert!(rule(Repeat::Monthly { day: 32 }).validate().is_err());
+    }
+
+    #[test]
+    fn finds_next_dates() {
+        // 2024-03-15 is a Friday
+        let today = date(2024, 3, 15);
+        assert_eq!(next_date(&Repeat::Daily, None, today).unwrap(), date(2024, 3, 16));
+        assert_eq!(next_date(&Repeat::Daily, Some(date(2024, 3, 20)), today).unwrap(), date(2024, 3, 21));
+        let weekly = Repeat::Weekly { weekdays: vec![0, 4] };
+        assert_eq!(next_date(&weekly, None, today).unwrap(), date(2024, 3, 18));
+        assert_eq!(next_date(&Repeat::Monthly { day: 31 }, None, date(2024, 1, 31)).unwrap(), date(2024, 2, 29));
+        assert_eq!(next_date(&Repeat::Monthly { day: 10 }, None, date(2024, 12, 20)).unwrap(), date(2025, 1, 10));
+        // Counted from the completion, not from an overdue due date
+        let after = Repeat::AfterCompletion { days: 3 };
+        assert_eq!(next_date(&after, Some(date(2024, 3, 1)), today).unwrap(), date(2024, 3, 18));
+    }
+
+    #[test]
+    fn fails_past_the_last_date() {
+        for repeat in [
+            Repeat::Daily,
+            Repeat::Weekly { weekdays: vec![0] },
+            Repeat::Monthly { day: 1 },
+            Repeat::AfterCompletion { days: 200_000_000 },
+        ] {
+            assert!(next_date(&repeat, None, NaiveDate::MAX).is_err(), "{:?}", repeat);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Note revision history.
+//!
+//! `save_note` copies the text it is about to replace into `note_revisions`
+//! when the newest copy is older than `SNAPSHOT_INTERVAL_MS`, so a burst of
+//! typing leaves one revision rather than one per keystroke. Revisions past
+//! `MAX_REVISIONS` per note or older than `MAX_AGE_DAYS` are dropped. This
+//! module holds that policy and the line diff the revision browser shows.
+
+use serde::{Deserialize, Serialize};
+
+pub const SNAPSHOT_INTERVAL_MS: i64 = 5 * 60 * 1000;
+
+/// Revisions kept per note, newest first.
+pub const MAX_REVISIONS: u32 = 50;
+
+pub const MAX_AGE_DAYS: i64 = 30;
+
+/// Beyond this many line pairs the diff gives up on finding common lines
+/// and shows the whole text as replaced.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRevision {
+    pub id: u32,
+    pub note_id: u32,
+    /// Unix time in milliseconds.
+    pub created_at: i64,
+    /// The first non-blank line, for the list.
+    pub preview: String,
+    pub length: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Same,
+    /// Only in the current text.
+    Added,
+    /// Only in the revision.
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+pub fn preview(content: &str) -> String {
+    content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default().to_string()
+}
+
+/// Line diff turning `old` into `new`, using the longest common
+/// subsequence of the lines between a shared head and tail.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let head = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let tail = old[head..]
+        .iter()
+        .rev()
+        .zip(new[head..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (&old[head..old.len() - tail], &new[head..new.len() - tail]);
+
+    let line = |op, text: &str| DiffLine { op, text: text.to_string() };
+    let mut diff: Vec<DiffLine> = old[..head].iter().map(|l| line(DiffOp::Same, l)).collect();
+
+    if old_mid.len() * new_mid.len() > MAX_DIFF_CELLS {
+        diff.extend(old_mid.iter().map(|l| line(DiffOp::Removed, l)));
+        diff.extend(new_mid.iter().map(|l| line(DiffOp::Added, l)));
+    } else {
+        // lcs[i][j]: common lines of old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                diff.push(line(DiffOp::Same, old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                diff.push(line(DiffOp::Removed, old_mid[i]));
+                i += 1;
+            } else {
+                diff.push(line(DiffOp::Added, new_mid[j]));
+                j += 1;
+            }
+        }
+    }
+
+    diff.extend(old[old.len() - tail..].iter().map(|l| line(DiffOp::Same, l)));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(diff: &[DiffLine]) -> Vec<(DiffOp, &str)> {
+        diff.iter().map(|l| (l.op, l.text.as_str())).collect()
+    }
+
+    #[test]
+    fn unchanged_text_is_all_same() {
+        let diff = diff_lines("one\ntwo\n", "one\ntwo\n");
+        assert_eq!(ops(&diff), vec![(DiffOp::Same, "one"), (DiffOp::Same, "two")]);
+    }
+
+    #[test]
+    fn finds_insertions_and_deletions() {
+        let diff = diff_lines("one\ntwo\nthree", "one\nthree\nfour");
+        assert_eq!(
+            ops(&diff),
+            vec![(DiffOp::Same, "one"), (DiffOp::Removed, "two"), (DiffOp::Same, "three"), (DiffOp::Added, "four")]
+        );
+
+        let diff = diff_lines("a\nb\nc", "a\nx\nb\ny\nc");
+        assert_eq!(
+            ops(&diff),
+            vec![
+                (DiffOp::Same, "a"),
+                (DiffOp::Added, "x"),
+                (DiffOp::Same, "b"),
+                (DiffOp::Added, "y"),
+                (DiffOp::Same, "c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn handles_empty_text() {
+        assert!(diff_lines("", "").is_empty());
+        assert_eq!(ops(&diff_lines("", "new")), vec![(DiffOp::Added, "new")]);
+        assert_eq!(ops(&diff_lines("old\n", "")), vec![(DiffOp::Removed, "old")]);
+        // Repeated lines do not confuse the shared head and tail
+        assert_eq!(ops(&diff_lines("x\nx", "x")), vec![(DiffOp::Same, "x"), (DiffOp::Removed, "x")]);
+    }
+
+    #[test]
+    fn previews_the_first_non_blank_line() {
+        assert_eq!(preview("\n  \n  Title  \nBody"), "Title");
+        assert_eq!(preview(""), "");
+    }
+}
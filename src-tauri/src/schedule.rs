@@ -0,0 +1,151 @@
+//! Automatic reset schedules for checklists.
+//!
+//! A schedule resets a whole list or one subtree at a fixed local time. The
+//! store keeps the time of the last reset per schedule, and `last_occurrence`
+//! tells whether a reset has become due since then, which also covers the
+//! app having been closed at reset time.
+
+use crate::error::{AppError, Result};
+use chrono::{Datelike, Duration, Local, NaiveDateTime, NaiveTime, Offset, TimeZone};
+use serde::{Deserialize, Serialize};
+
+pub const TIME_FORMAT: &str = "%H:%M";
+
+/// How often the scheduler thread looks for due resets.
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ResetFrequency {
+    Daily,
+    /// 0 = Monday .. 6 = Sunday.
+    Weekly { weekday: u8 },
+}
+
+impl ResetFrequency {
+    pub fn validate(&self) -> Result<()> {
+        if let ResetFrequency::Weekly { weekday } = self {
+            if *weekday > 6 {
+                return Err(AppError::InvalidInput(format!("Invalid weekday {}", weekday)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The `weekday` column, NULL for daily schedules.
+    pub fn weekday(&self) -> Option<u8> {
+        match self {
+            ResetFrequency::Daily => None,
+            ResetFrequency::Weekly { weekday } => Some(*weekday),
+        }
+    }
+
+    pub fn from_weekday(weekday: Option<u8>) -> Self {
+        match weekday {
+            Some(weekday) => ResetFrequency::Weekly { weekday },
+            None => ResetFrequency::Daily,
+        }
+    }
+}
+
+/// Validates an `HH:MM` time and returns it normalized.
+pub fn parse_time(value: &str) -> Result<String> {
+    NaiveTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map(|t| t.format(TIME_FORMAT).to_string())
+        .map_err(|_| AppError::InvalidInput(format!("Invalid time \"{}\", expected HH:MM", value)))
+}
+
+/// The most recent moment at or before `now` the schedule fired, as Unix
+/// milliseconds. `None` if the time is unreadable.
+pub fn last_occurrence(frequency: &ResetFrequency, time: &str, now: NaiveDateTime) -> Option<i64> {
+    last_occurrence_in(&Local, frequency, time, now)
+}
+
+fn last_occurrence_in<Tz: TimeZone>(
+    tz: &Tz,
+    frequency: &ResetFrequency,
+    time: &str,
+    now: NaiveDateTime,
+) -> Option<i64> {
+    let time = NaiveTime::parse_from_str(time, TIME_FORMAT).ok()?;
+    let today = now.date();
+
+    let (date, period) = match frequency {
+        ResetFrequency::Daily => (today, Duration::days(1)),
+        ResetFrequency::Weekly { weekday } => {
+            let days_back = (today.weekday().num_days_from_monday() as i64 - i64::from(*weekday)).rem_euclid(7);
+            (today - Duration::days(days_back), Duration::days(7))
+        }
+    };
+    let mut occurrence = date.and_time(time);
+    if occurrence > now {
+        occurrence -= period;
+    }
+
+    resolve(tz, occurrence)
+}
+
+/// A local time as Unix milliseconds. A time skipped by a DST change is
+/// read with the offset from before the change, which moves it forward by
+/// the length of the gap, so the reset still fires that day.
+fn resolve<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<i64> {
+    if let Some(t) = tz.from_local_datetime(&local).earliest() {
+        return Some(t.timestamp_millis());
+    }
+    let offset = tz.from_local_datetime(&(local - Duration::days(1))).earliest()?.offset().fix();
+    Some((local - Duration::seconds(i64::from(offset.local_minus_utc()))).and_utc().timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use chrono_tz::Europe::Berlin;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        let date = NaiveDate::parse_from_str(date, crate::db::DATE_FORMAT).unwrap();
+        date.and_time(NaiveTime::parse_from_str(time, TIME_FORMAT).unwrap())
+    }
+
+    fn berlin(date: &str, time: &str) -> i64 {
+        Berlin.from_local_datetime(&at(date, time)).unwrap().timestamp_millis()
+    }
+
+    #[test]
+    fn daily_schedules_fire_once_a_day() {
+        let daily = ResetFrequency::Daily;
+        let last = |now| last_occurrence_in(&Berlin, &daily, "06:30", now);
+        assert_eq!(last(at("2026-10-14", "07:00")), Some(berlin("2026-10-14", "06:30")));
+        assert_eq!(last(at("2026-10-14", "06:30")), Some(berlin("2026-10-14", "06:30")));
+        assert_eq!(last(at("2026-10-14", "06:29")), Some(berlin("2026-10-13", "06:30")));
+        // Across the end of a month
+        assert_eq!(last(at("2026-11-01", "00:00")), Some(berlin("2026-10-31", "06:30")));
+        assert_eq!(last_occurrence_in(&Berlin, &daily, "25:00", at("2026-10-14", "07:00")), None);
+    }
+
+    #[test]
+    fn weekly_schedules_fire_on_their_weekday() {
+        // 2026-10-14 is a Wednesday
+        let wednesday = ResetFrequency::Weekly { weekday: 2 };
+        let last = |now| last_occurrence_in(&Berlin, &wednesday, "08:00", now);
+        assert_eq!(last(at("2026-10-14", "09:00")), Some(berlin("2026-10-14", "08:00")));
+        assert_eq!(last(at("2026-10-14", "07:00")), Some(berlin("2026-10-07", "08:00")));
+        assert_eq!(last(at("2026-10-18", "12:00")), Some(berlin("2026-10-14", "08:00")));
+        // Back into the previous month
+        assert_eq!(last(at("2026-10-01", "12:00")), Some(berlin("2026-09-30", "08:00")));
+        let monday = ResetFrequency::Weekly { weekday: 0 };
+        let last = last_occurrence_in(&Berlin, &monday, "08:00", at("2026-11-01", "12:00"));
+        assert_eq!(last, Some(berlin("2026-10-26", "08:00")));
+    }
+
+    #[test]
+    fn times_in_a_dst_gap_move_past_it() {
+        // Berlin skips from 02:00 to 03:00 on 2026-03-29
+        let last = last_occurrence_in(&Berlin, &ResetFrequency::Daily, "02:30", at("2026-03-29", "12:00"));
+        assert_eq!(last, Some(berlin("2026-03-29", "03:30")));
+        // The repeated hour in autumn fires at its first pass
+        let last = last_occurrence_in(&Berlin, &ResetFrequency::Daily, "02:30", at("2026-10-25", "12:00"));
+        let first = Berlin.from_local_datetime(&at("2026-10-25", "02:30")).earliest().unwrap();
+        assert_eq!(last, Some(first.timestamp_millis()));
+    }
+}
@@ -0,0 +1,64 @@
+//! Full-text search over todos and notes.
+//!
+//! `todos_fts` and `notes_fts` are FTS5 indexes kept current by triggers
+//! (migrations 11 and 15). This module turns what the user typed into an
+//! FTS5 query and defines the hits the store returns.
+
+use serde::{Deserialize, Serialize};
+
+/// Wrapped around every match in a snippet. Control characters rather than
+/// HTML so the text itself never has to be trusted as markup.
+pub const HIGHLIGHT_START: &str = "\u{2}";
+pub const HIGHLIGHT_END: &str = "\u{3}";
+
+pub const DEFAULT_LIMIT: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HitKind {
+    Todo,
+    Note,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: HitKind,
+    pub id: u32,
+    /// The list a todo lives in, `None` for notes.
+    pub list_id: Option<u32>,
+    pub list_name: Option<String>,
+    /// The matching text around the hit, see `HIGHLIGHT_START`.
+    pub snippet: String,
+    /// Texts of the todo's ancestors, root first, or the note's title.
+    pub path: Vec<String>,
+    /// bm25 score, lower is better.
+    pub rank: f64,
+}
+
+/// Builds an FTS5 query matching items that contain every word of `input`,
+/// the last one as a prefix so results show up while typing. Words are
+/// quoted, so FTS5 operators typed by the user are searched for literally.
+/// `None` if there is nothing to search for.
+pub fn fts_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_words_and_prefixes_the_last() {
+        assert_eq!(fts_query("buy milk").as_deref(), Some("\"buy\" \"milk\"*"));
+        assert_eq!(fts_query("say \"hi\"").as_deref(), Some("\"say\" \"\"\"hi\"\"\"*"));
+        assert_eq!(fts_query("  - * ( "), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Completion statistics for the stats view.
+//!
+//! The store collects the dates of `completed` events from `todo_events`,
+//! the functions here bucket them into days and weeks and measure streaks.
+//! All dates are local calendar dates.
+
+use crate::db::DATE_FORMAT;
+use crate::recurrence::{RecurrenceRule, Repeat};
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// The longest span the stats view can ask for, in days.
+pub const MAX_DAYS: u32 = 5 * 366;
+
+/// Completions on one day, or in the week starting on `date` (a Monday).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayCount {
+    pub date: String,
+    pub count: u32,
+}
+
+/// The unit a streak is counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// Daily items and plain countdowns count in days, weekly and monthly
+    /// rules in their own period, so skipping the days in between does not
+    /// break the streak.
+    pub fn for_rule(rule: Option<&RecurrenceRule>) -> Self {
+        match rule.map(|r| &r.repeat) {
+            None | Some(Repeat::Daily) => Period::Day,
+            Some(Repeat::Weekly { .. }) => Period::Week,
+            Some(Repeat::Monthly { .. }) => Period::Month,
+            Some(Repeat::AfterCompletion { days }) => match days {
+                1 => Period::Day,
+                2..=7 => Period::Week,
+                _ => Period::Month,
+            },
+        }
+    }
+
+    /// Consecutive periods have consecutive indexes.
+    fn index(self, date: NaiveDate) -> i64 {
+        match self {
+            Period::Day => i64::from(date.num_days_from_ce()),
+            Period::Week => i64::from(week_start(date).num_days_from_ce()) / 7,
+            Period::Month => i64::from(date.year()) * 12 + i64::from(date.month0()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Streak {
+    pub todo_id: u32,
+    pub list_id: u32,
+    pub text: String,
+    pub period: Period,
+    pub current: u32,
+    pub longest: u32,
+}
+
+/// Completed versus all live items of a list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListRate {
+    pub list_id: u32,
+    pub name: String,
+    pub completed: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stats {
+    /// One entry per day up to today, oldest first. Starts on a Monday so
+    /// the heatmap can lay it out in week columns.
+    pub days: Vec<DayCount>,
+    pub weeks: Vec<DayCount>,
+    pub streaks: Vec<Streak>,
+    pub lists: Vec<ListRate>,
+}
+
+/// The local date of a Unix millisecond timestamp.
+pub fn local_date(millis: i64) -> Option<NaiveDate> {
+    Local.timestamp_millis_opt(millis).earliest().map(|t| t.date_naive())
+}
+
+/// Local midnight at the start of `date` as Unix milliseconds.
+pub fn start_of_day(date: NaiveDate) -> i64 {
+    Local
+        .from_local_datetime(&date.and_time(chrono::NaiveTime::MIN))
+        .earliest()
+        .map_or(0, |t| t.timestamp_millis())
+}
+
+/// The Monday of the week `date` falls in.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+/// Counts `dates` per day from `from` through `to`, days without
+/// completions included.
+pub fn daily_counts(dates: &[NaiveDate], from: NaiveDate, to: NaiveDate) -> Vec<DayCount> {
+    from.iter_days()
+        .take_while(|d| *d <= to)
+        .map(|day| DayCount {
+            date: day.format(DATE_FORMAT).to_string(),
+            count: dates.iter().filter(|d| **d == day).count() as u32,
+        })
+        .collect()
+}
+
+/// Sums daily counts that start on a Monday into weeks.
+pub fn weekly_counts(days: &[DayCount]) -> Vec<DayCount> {
+    days.chunks(7)
+        .map(|week| DayCount {
+            date: week[0].date.clone(),
+            count: week.iter().map(|d| d.count).sum(),
+        })
+        .collect()
+}
+
+/// The current and longest run of consecutive periods with at least one
+/// completion. The current period only ends the streak once it is over, so
+/// a daily habit not yet done today still shows yesterday's streak.
+pub fn streak(dates: &[NaiveDate], period: Period, today: NaiveDate) -> (u32, u32) {
+    let periods: BTreeSet<i64> = dates.iter().map(|d| period.index(*d)).collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for &p in &periods {
+        run = if previous == Some(p - 1) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(p);
+    }
+
+    let now = period.index(today);
+    let mut end = if periods.contains(&now) { now } else { now - 1 };
+    let mut current = 0;
+    while periods.contains(&end) {
+        current += 1;
+        end -= 1;
+    }
+
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    fn dates(list: &[&str]) -> Vec<NaiveDate> {
+        list.iter().map(|d| date(d)).collect()
+    }
+
+    #[test]
+    fn finds_week_starts() {
+        // 2026-10-12 is a Monday
+        assert_eq!(week_start(date("2026-10-12")), date("2026-10-12"));
+        assert_eq!(week_start(date("2026-10-18")), date("2026-10-12"));
+        assert_eq!(week_start(date("2026-01-01")), date("2025-12-29"));
+    }
+
+    #[test]
+    fn daily_streak_survives_until_the_day_is_over() {
+        let done = dates(&["2026-10-14", "2026-10-15", "2026-10-16"]);
+        assert_eq!(streak(&done, Period::Day, date("2026-10-16")), (3, 3));
+        // Not done yet today
+        assert_eq!(streak(&done, Period::Day, date("2026-10-17")), (3, 3));
+        // A whole day missed
+        assert_eq!(streak(&done, Period::Day, date("2026-10-18")), (0, 3));
+    }
+
+    #[test]
+    fn gaps_end_a_run() {
+        let done = dates(&["2026-10-01", "2026-10-02", "2026-10-03", "2026-10-05", "2026-10-06", "2026-10-06"]);
+        assert_eq!(streak(&done, Period::Day, date("2026-10-06")), (2, 3));
+        assert_eq!(streak(&[], Period::Day, date("2026-10-06")), (0, 0));
+    }
+
+    #[test]
+    fn weekly_and_monthly_streaks_count_periods() {
+        // Different weekdays in three consecutive weeks
+        let done = dates(&["2026-09-28", "2026-10-09", "2026-10-11", "2026-10-12"]);
+        assert_eq!(streak(&done, Period::Week, date("2026-10-17")), (3, 3));
+        assert_eq!(streak(&done, Period::Week, date("2026-10-19")), (3, 3));
+        assert_eq!(streak(&done, Period::Week, date("2026-10-26")), (0, 3));
+
+        let done = dates(&["2025-11-30", "2025-12-01", "2026-01-31"]);
+        assert_eq!(streak(&done, Period::Month, date("2026-02-10")), (3, 3));
+    }
+
+    #[test]
+    fn buckets_days_into_weeks() {
+        let from = date("2026-10-05");
+        let days = daily_counts(&dates(&["2026-10-05", "2026-10-05", "2026-10-13"]), from, date("2026-10-14"));
+        assert_eq!(days.len(), 10);
+        assert_eq!(days[0].count, 2);
+        let weeks = weekly_counts(&days);
+        let weeks: Vec<(&str, u32)> = weeks.iter().map(|w| (w.date.as_str(), w.count)).collect();
+        assert_eq!(weeks, vec![("2026-10-05", 2), ("2026-10-12", 1)]);
+    }
+}
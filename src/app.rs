@@ -3,14 +3,45 @@ use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos::html::ElementChild;
 use web_sys::SubmitEvent;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use wasm_bindgen::prelude::*;
 use regex::Regex;
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], catch)]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>) -> JsValue;
+}
+
+/// Mirrors the backend `AppError`: `{ "kind": "not_found", "message": "..." }`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AppError {
+    pub kind: String,
+    pub message: String,
+}
+
+impl AppError {
+    fn from_js(value: JsValue) -> Self {
+        serde_wasm_bindgen::from_value(value.clone()).unwrap_or_else(|_| AppError {
+            // Tauri itself rejects with a plain string, e.g. for missing state
+            kind: "unknown".to_string(),
+            message: value.as_string().unwrap_or_else(|| format!("{:?}", value)),
+        })
+    }
+}
+
+/// Invokes a backend command and decodes its result.
+async fn invoke_cmd<T: DeserializeOwned>(cmd: &str, args: JsValue) -> Result<T, AppError> {
+    let value = invoke(cmd, args).await.map_err(AppError::from_js)?;
+    serde_wasm_bindgen::from_value(value).map_err(|e| AppError {
+        kind: "decode".to_string(),
+        message: format!("{}: {}", cmd, e),
+    })
 }
 
 #[derive(Serialize, Deserialize)]
@@ -19,27 +50,246 @@ struct SetAlwaysOnTopArgs {
     always_on_top: bool,
 }
 
+/// Mirrors the backend `db::NoteView`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NoteView {
+    #[default]
+    Edit,
+    Preview,
+    Split,
+}
+
+impl NoteView {
+    const ALL: [NoteView; 3] = [NoteView::Edit, NoteView::Preview, NoteView::Split];
+
+    fn icon(self) -> &'static str {
+        match self {
+            NoteView::Edit => "✏️",
+            NoteView::Preview => "👁️",
+            NoteView::Split => "◫",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            NoteView::Edit => "Edit",
+            NoteView::Preview => "Preview",
+            NoteView::Split => "Edit and preview side by side",
+        }
+    }
+}
+
+/// Mirrors the backend `db::Tab`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Tab {
+    Todos,
+    Notes,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NoteViewArgs {
+    view: NoteView,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TabArgs {
+    tab: Tab,
+}
+
+/// A note as listed in the note picker, see the backend `db::NoteSummary`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoteSummary {
+    pub id: u32,
+    pub title: String,
+    pub position: i32,
+    pub updated_at: Option<i64>,
+    /// Whether the note has a sticky window of its own.
+    pub windowed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub id: u32,
+    pub title: String,
+    pub content: String,
+    pub updated_at: Option<i64>,
+}
+
+/// A named todo list, see the backend `db::List`.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct List {
+    pub id: u32,
+    pub name: String,
+    pub position: i32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct TodoItem {
     pub id: u32,
+    #[serde(default)]
+    pub list_id: u32,
     pub text: String,
     pub completed: bool,
     pub parent_id: Option<u32>,
     pub position: i32,
     pub target_count: Option<i32>,
     pub current_count: i32,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    pub due_at: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    pub scheduled_for: Option<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub recurrence: Option<RecurrenceRule>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Shown only as an ancestor of a saved view's match.
+    #[serde(skip)]
+    pub is_context: bool,
+}
+
+/// Mirrors the backend `recurrence::Repeat`. Weekdays are 0 = Monday.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Repeat {
+    Daily,
+    Weekly { weekdays: Vec<u8> },
+    Monthly { day: u8 },
+    AfterCompletion { days: u32 },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceMode {
+    #[default]
+    Reschedule,
+    Spawn,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    pub repeat: Repeat,
+    #[serde(default)]
+    pub mode: RecurrenceMode,
+}
+
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+impl Repeat {
+    fn kind(&self) -> &'static str {
+        match self {
+            Repeat::Daily => "daily",
+            Repeat::Weekly { .. } => "weekly",
+            Repeat::Monthly { .. } => "monthly",
+            Repeat::AfterCompletion { .. } => "after_completion",
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Repeat::Daily => "Repeats daily".to_string(),
+            Repeat::Weekly { weekdays } => format!(
+                "Repeats every {}",
+                weekdays
+                    .iter()
+                    .filter_map(|d| WEEKDAY_LABELS.get(*d as usize))
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Repeat::Monthly { day } => format!("Repeats monthly on day {}", day),
+            Repeat::AfterCompletion { days } => format!("Repeats {} day(s) after completion", days),
+        }
+    }
+}
+
+/// Mirrors the backend `Priority`, serialized as `"none"` .. `"urgent"`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    const ALL: [Priority; 5] = [Priority::None, Priority::Low, Priority::Medium, Priority::High, Priority::Urgent];
+
+    fn key(self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Priority::None => "–",
+            Priority::Low => "Low",
+            Priority::Medium => "Med",
+            Priority::High => "High",
+            Priority::Urgent => "Urgent",
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            Priority::None => "text-gray-300",
+            Priority::Low => "text-sky-600",
+            Priority::Medium => "text-amber-600",
+            Priority::High => "text-orange-600 font-bold",
+            Priority::Urgent => "text-red-600 font-bold",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 struct SaveNoteArgs {
+    id: u32,
     content: String,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListIdArgs {
+    list_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct AddTodoArgs {
+    list_id: u32,
     text: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportMarkdownArgs {
+    list_id: u32,
+    parent_id: Option<u32>,
+    markdown: String,
+}
+
+/// Whether text typed or pasted into the add form is a checklist to import
+/// rather than a single item: several lines, or one `- [ ]` line.
+fn is_checklist(text: &str) -> bool {
+    let text = text.trim();
+    text.contains('\n') || ["- [", "* [", "+ ["].iter().any(|marker| text.starts_with(marker))
+}
+
 #[derive(Serialize, Deserialize)]
 struct UpdateTodoArgs {
     id: u32,
@@ -47,8 +297,10 @@ struct UpdateTodoArgs {
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct RemoveTodoArgs {
     id: u32,
+    keep_children: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -57,6 +309,31 @@ struct SetTodoCountArgs {
     count: Option<i32>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetTodoDatesArgs {
+    id: u32,
+    due_at: Option<String>,
+    scheduled_for: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SetTodoRecurrenceArgs {
+    id: u32,
+    rule: Option<RecurrenceRule>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SetTodoPriorityArgs {
+    id: u32,
+    priority: Priority,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SetSortModeArgs {
+    mode: String,
+}
+
 #[derive(Serialize, Deserialize)]
 struct DecrementTodoArgs {
     id: u32,
@@ -75,8 +352,6 @@ struct LogArgs {
     msg: String,
 }
 
-#[derive(Serialize, Deserialize)]
-struct ResetAllArgs {}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct WindowState {
@@ -139,18 +414,208 @@ fn render_todo_markdown(text: &str) -> String {
         .replace("%%COLOR_GREEN_START%%", "<span class=\"text-green-500\">")
         .replace("%%COLOR_BLUE_START%%", "<span class=\"text-blue-500\">")
         .replace("%%COLOR_END%%", "</span>");
-        
+
+    // 4. `#tags` become chips. Same rules as the backend parser: letters,
+    // digits, `_`, `-` and `/`, and not just a number
+    if let Ok(re) = Regex::new(r"(^|[\s>])#([\w/-]+)") {
+        final_html = re
+            .replace_all(&final_html, |caps: &regex::Captures| {
+                let name = &caps[2];
+                if name.chars().all(|c| c.is_ascii_digit()) {
+                    return caps[0].to_string();
+                }
+                format!(
+                    "{}<span class=\"inline-block px-1 rounded bg-indigo-100 text-indigo-700 text-xs\">#{}</span>",
+                    &caps[1], name
+                )
+            })
+            .to_string();
+    }
+
     final_html
 }
 
+/// Renders block Markdown for notes and todo descriptions, unlike the
+/// inline rendering of a todo's text.
+fn render_block_markdown(markdown: &str) -> String {
+    let options = pulldown_cmark::Options::ENABLE_STRIKETHROUGH
+        | pulldown_cmark::Options::ENABLE_TABLES
+        | pulldown_cmark::Options::ENABLE_TASKLISTS;
+    let parser = pulldown_cmark::Parser::new_ext(markdown, options);
+    let mut html_output = String::new();
+    pulldown_cmark::html::push_html(&mut html_output, parser);
+    html_output
+}
+
+/// How long typing has to pause before a note is saved.
+const NOTE_SAVE_DELAY: Duration = Duration::from_millis(800);
+
+/// Note text not yet confirmed by the backend, kept in localStorage so a
+/// crash between a keystroke and the debounced save loses nothing.
+#[derive(Serialize, Deserialize)]
+struct NoteDraft {
+    content: String,
+    /// Unix time in milliseconds.
+    written_at: f64,
+}
+
+fn draft_key(note_id: u32) -> String {
+    format!("note-draft-{}", note_id)
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+fn write_draft(note_id: u32, content: &str) {
+    let draft = NoteDraft { content: content.to_string(), written_at: js_sys::Date::now() };
+    let json = serde_wasm_bindgen::to_value(&draft).ok().and_then(|v| js_sys::JSON::stringify(&v).ok());
+    if let (Some(storage), Some(json)) = (local_storage(), json) {
+        let _ = storage.set_item(&draft_key(note_id), &String::from(json));
+    }
+}
+
+fn read_draft(note_id: u32) -> Option<NoteDraft> {
+    let json = local_storage()?.get_item(&draft_key(note_id)).ok().flatten()?;
+    serde_wasm_bindgen::from_value(js_sys::JSON::parse(&json).ok()?).ok()
+}
+
+fn clear_draft(note_id: u32) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(&draft_key(note_id));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SaveStatus {
+    Saved,
+    /// Typed, waiting for the pause before saving.
+    Pending,
+    Saving,
+    Failed,
+}
+
+impl SaveStatus {
+    fn label(self) -> &'static str {
+        match self {
+            SaveStatus::Saved => "Saved",
+            SaveStatus::Pending | SaveStatus::Saving => "Saving…",
+            SaveStatus::Failed => "Not saved",
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            SaveStatus::Saved => "text-yellow-600",
+            SaveStatus::Pending | SaveStatus::Saving => "text-yellow-700 italic",
+            SaveStatus::Failed => "text-red-600 font-bold",
+        }
+    }
+}
+
+/// Today's local date as `YYYY-MM-DD`, comparable with stored dates as text.
+fn today_string() -> String {
+    let now = js_sys::Date::new_0();
+    format!("{:04}-{:02}-{:02}", now.get_full_year(), now.get_month() + 1, now.get_date())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DueStatus {
+    Overdue,
+    Today,
+    Upcoming,
+}
+
+fn due_status(todo: &TodoItem) -> Option<DueStatus> {
+    if todo.completed {
+        return None;
+    }
+    let due = todo.due_at.as_deref()?;
+    let today = today_string();
+    Some(match due.cmp(today.as_str()) {
+        std::cmp::Ordering::Less => DueStatus::Overdue,
+        std::cmp::Ordering::Equal => DueStatus::Today,
+        std::cmp::Ordering::Greater => DueStatus::Upcoming,
+    })
+}
+
+/// `Today`, `MM-DD` within the current year, the full date otherwise.
+fn short_date(date: &str) -> String {
+    let today = today_string();
+    if date == today {
+        "Today".to_string()
+    } else if date.get(..4) == today.get(..4) {
+        date.get(5..).unwrap_or(date).to_string()
+    } else {
+        date.to_string()
+    }
+}
+
+fn date_badges(todo: &TodoItem) -> impl IntoView {
+    let due = todo.due_at.as_deref().map(|date| {
+        let (class, title) = match due_status(todo) {
+            Some(DueStatus::Overdue) => ("bg-red-100 text-red-700", "Overdue"),
+            Some(DueStatus::Today) => ("bg-amber-100 text-amber-700", "Due today"),
+            _ => ("bg-gray-100 text-gray-500", "Due"),
+        };
+        view! {
+            <span class=format!("px-1 rounded text-xs whitespace-nowrap {}", class) title=title>
+                {format!("📅 {}", short_date(date))}
+            </span>
+        }
+    });
+    let scheduled = todo.scheduled_for.as_deref().map(|date| view! {
+        <span class="px-1 rounded text-xs whitespace-nowrap bg-blue-50 text-blue-500" title="Scheduled">
+            {format!("⏱ {}", short_date(date))}
+        </span>
+    });
+    let repeats = todo.recurrence.as_ref().map(|rule| {
+        let mut title = rule.repeat.describe();
+        if rule.mode == RecurrenceMode::Spawn {
+            title.push_str(", as a new copy");
+        }
+        view! { <span class="text-xs text-gray-400" title=title>"🔁"</span> }
+    });
+    (due, scheduled, repeats)
+}
+
+/// Whether focus is moving to another field of the same item's editor.
+fn focus_stays_in_editor(ev: &web_sys::FocusEvent, id: u32) -> bool {
+    ev.related_target()
+        .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+        .and_then(|el| el.get_attribute("data-todo-editor"))
+        .is_some_and(|editor| editor == id.to_string())
+}
+
 #[component]
 pub fn App() -> impl IntoView {
     let (pinned, set_pinned) = signal(false);
     let (content, set_content) = signal(String::new());
-    let (editing, set_editing) = signal(true);
+    let (note_view, set_note_view) = signal(NoteView::Edit);
     let (todos, set_todos) = signal(Vec::<TodoItem>::new());
     let (mode, set_mode) = signal("todo");
+    let (lists, set_lists) = signal(Vec::<List>::new());
+    // 0 until the backend has told us which list to show
+    let (active_list, set_active_list) = signal(0u32);
+    let (notes, set_notes) = signal(Vec::<NoteSummary>::new());
+    // 0 until loaded, like active_list
+    let (active_note, set_active_note) = signal(0u32);
+    // Set in a note's own window, which shows only that note
+    let (window_note, set_window_note) = signal(None::<u32>);
+    let (show_revisions, set_show_revisions) = signal(false);
+    let (save_status, set_save_status) = signal(SaveStatus::Saved);
+    // Note text typed but not yet written, with the note it belongs to
+    let pending_note = StoredValue::new(None::<(u32, String)>);
+    // Bumped on every keystroke so only the last one's timer saves
+    let save_generation = StoredValue::new(0u32);
+    // Set after a draft was recovered, with the text it replaced
+    let (recovered_note, set_recovered_note) = signal(None::<(u32, String)>);
+    // "manual" or "priority", applied by the backend when loading todos
+    let (sort_mode, set_sort_mode) = signal("manual".to_string());
     let (show_markdown_tip, set_show_markdown_tip) = signal(false);
+    let (startup_error, set_startup_error) = signal(None::<String>);
+    // Non-blocking error toast; the counter lets a newer toast outlive an older timeout
+    let (error_toast, set_error_toast) = signal(None::<(u32, String)>);
     
     // Global drag state
     let (dragging_id, set_dragging_id) = signal(None::<u32>);
@@ -159,32 +624,267 @@ pub fn App() -> impl IntoView {
 
     // Todo editing state
     let (editing_todo_id, set_editing_todo_id) = signal(None::<u32>);
+    // Saved view shown instead of the active list
+    let (active_view, set_active_view) = signal(None::<SavedView>);
+    // Item a search result jumped to, outlined for a moment
+    let (highlighted_id, set_highlighted_id) = signal(None::<u32>);
 
     let log = move |msg: String| {
         spawn_local(async move {
             let args = serde_wasm_bindgen::to_value(&LogArgs { msg }).unwrap();
-            invoke("log_message", args).await;
+            let _ = invoke("log_message", args).await;
+        });
+    };
+
+    let show_error = move |err: AppError| {
+        web_sys::console::error_1(&JsValue::from_str(&format!("[{}] {}", err.kind, err.message)));
+        let toast_id = error_toast.get_untracked().map(|(id, _)| id + 1).unwrap_or(0);
+        set_error_toast.set(Some((toast_id, err.message)));
+        set_timeout(
+            move || {
+                if error_toast.get_untracked().is_some_and(|(id, _)| id == toast_id) {
+                    set_error_toast.set(None);
+                }
+            },
+            Duration::from_secs(4),
+        );
+    };
+
+    // Replaces the local list with the backend's view of it
+    let reload_todos = move || async move {
+        if let Some(saved_view) = active_view.get_untracked() {
+            let args = serde_wasm_bindgen::to_value(&RunQueryArgs { query: saved_view.query }).unwrap();
+            match invoke_cmd::<QueryResult>("run_query", args).await {
+                Ok(result) => set_todos.set(result.into_todos(saved_view.layout)),
+                Err(e) => show_error(e),
+            }
+            return;
+        }
+        let args = serde_wasm_bindgen::to_value(&ListIdArgs { list_id: active_list.get_untracked() }).unwrap();
+        match invoke_cmd::<Vec<TodoItem>>("load_todos", args).await {
+            Ok(saved_todos) => set_todos.set(saved_todos),
+            Err(e) => show_error(e),
+        }
+    };
+
+    // Reloads the list names, falling back to the first list if the active
+    // one is gone (deleted, or its creation undone)
+    let reload_lists = move || async move {
+        match invoke_cmd::<Vec<List>>("load_lists", JsValue::NULL).await {
+            Ok(saved_lists) => {
+                let active = active_list.get_untracked();
+                if !saved_lists.iter().any(|l| l.id == active) {
+                    if let Some(first) = saved_lists.first() {
+                        set_active_list.set(first.id);
+                    }
+                }
+                set_lists.set(saved_lists);
+            }
+            Err(e) => show_error(e),
+        }
+    };
+
+    // Writes the pending note text now instead of after the pause
+    let flush_note = move || async move {
+        let Some((id, text)) = pending_note.get_value() else {
+            return;
+        };
+        set_save_status.set(SaveStatus::Saving);
+        let args = serde_wasm_bindgen::to_value(&SaveNoteArgs { id, content: text.clone() }).unwrap();
+        match invoke_cmd::<()>("save_note_content", args).await {
+            Ok(()) => {
+                // Typing may have gone on while this was being written
+                if pending_note.with_value(|p| p.as_ref().is_some_and(|(i, t)| *i == id && *t == text)) {
+                    pending_note.set_value(None);
+                    clear_draft(id);
+                    set_save_status.set(SaveStatus::Saved);
+                } else {
+                    set_save_status.set(SaveStatus::Pending);
+                }
+            }
+            Err(e) => {
+                // The draft stays, so the text comes back at the next start
+                set_save_status.set(SaveStatus::Failed);
+                show_error(e);
+            }
+        }
+    };
+
+    // Shows a note, remembered for the next start unless this window is
+    // the note's own
+    let select_note = move |id: u32| {
+        set_active_note.set(id);
+        set_recovered_note.set(None);
+        spawn_local(async move {
+            flush_note().await;
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            if window_note.get_untracked().is_none() {
+                if let Err(e) = invoke_cmd::<()>("set_active_note", args.clone()).await {
+                    show_error(e);
+                }
+            }
+            match invoke_cmd::<Note>("load_note", args).await {
+                // A newer selection may have finished first
+                Ok(note) if active_note.get_untracked() == note.id => {
+                    // A draft newer than the saved text was never written,
+                    // the app must have quit in between
+                    let draft = read_draft(id).filter(|d| {
+                        d.content != note.content && d.written_at > note.updated_at.unwrap_or(0) as f64
+                    });
+                    match draft {
+                        Some(draft) => {
+                            set_content.set(draft.content.clone());
+                            set_recovered_note.set(Some((id, note.content)));
+                            pending_note.set_value(Some((id, draft.content)));
+                            flush_note().await;
+                        }
+                        None => {
+                            clear_draft(id);
+                            set_content.set(note.content);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    // Reloads the note titles, falling back to the first note if the active
+    // one was deleted
+    let reload_notes = move || async move {
+        match invoke_cmd::<Vec<NoteSummary>>("load_notes", JsValue::NULL).await {
+            Ok(saved_notes) => {
+                let active = active_note.get_untracked();
+                let fallback = saved_notes.first().map(|n| n.id).filter(|_| !saved_notes.iter().any(|n| n.id == active));
+                set_notes.set(saved_notes);
+                if let Some(first) = fallback {
+                    select_note(first);
+                }
+            }
+            Err(e) => show_error(e),
+        }
+    };
+
+    let select_list = move |id: u32| {
+        set_active_view.set(None);
+        set_active_list.set(id);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_active_list", args).await {
+                show_error(e);
+            }
+            reload_todos().await;
         });
     };
 
+    let open_view = move |saved_view: Option<SavedView>| {
+        set_active_view.set(saved_view);
+        set_mode.set("todo");
+        spawn_local(reload_todos());
+    };
+
+    let jump_to = move |hit: SearchHit| match hit.kind {
+        HitKind::Note => {
+            set_mode.set("note");
+            if hit.id != active_note.get_untracked() {
+                select_note(hit.id);
+            }
+        }
+        HitKind::Todo => {
+            set_mode.set("todo");
+            if let Some(list_id) = hit.list_id.filter(|l| *l != active_list.get_untracked()) {
+                select_list(list_id);
+            } else if active_view.get_untracked().is_some() {
+                open_view(None);
+            }
+            set_highlighted_id.set(Some(hit.id));
+            set_timeout(
+                move || {
+                    if highlighted_id.get_untracked() == Some(hit.id) {
+                        set_highlighted_id.set(None);
+                    }
+                },
+                Duration::from_secs(3),
+            );
+        }
+    };
+
+    // Scroll to the highlighted item once its list is loaded and rendered
+    Effect::new(move |_| {
+        let Some(id) = highlighted_id.get() else {
+            return;
+        };
+        if todos.with(|todos| todos.iter().any(|t| t.id == id)) {
+            request_animation_frame(move || {
+                let selector = format!("[data-todo-id=\"{}\"]", id);
+                if let Ok(Some(element)) = document().query_selector(&selector) {
+                    element.scroll_into_view();
+                }
+            });
+        }
+    });
+
     // Load initial data and window state
     Effect::new(move |_| {
         spawn_local(async move {
-            let saved_content: String =
-                serde_wasm_bindgen::from_value(invoke("load_note", JsValue::NULL).await)
-                    .unwrap_or_default();
-            set_content.set(saved_content);
-
-            let saved_todos: Vec<TodoItem> =
-                serde_wasm_bindgen::from_value(invoke("load_todos", JsValue::NULL).await)
-                    .unwrap_or_default();
-            set_todos.set(saved_todos);
+            let error: Option<AppError> = invoke_cmd("startup_error", JsValue::NULL)
+                .await
+                .ok()
+                .flatten();
+            if let Some(error) = error {
+                // The store is unavailable, every other command would fail too
+                set_startup_error.set(Some(error.message));
+                return;
+            }
+
+            match invoke_cmd::<NoteView>("get_note_view", JsValue::NULL).await {
+                Ok(view) => set_note_view.set(view),
+                Err(e) => show_error(e),
+            }
+
+            let own_note = invoke_cmd::<Option<u32>>("window_note", JsValue::NULL).await.ok().flatten();
+            set_window_note.set(own_note);
+            let note_id = match own_note {
+                Some(id) => {
+                    set_mode.set("note");
+                    Ok(id)
+                }
+                None => {
+                    if let Ok(Tab::Notes) = invoke_cmd::<Tab>("get_tab", JsValue::NULL).await {
+                        set_mode.set("note");
+                    }
+                    invoke_cmd::<u32>("get_active_note", JsValue::NULL).await
+                }
+            };
+            match note_id {
+                Ok(id) => select_note(id),
+                Err(e) => show_error(e),
+            }
+            reload_notes().await;
+
+            match invoke_cmd::<String>("get_sort_mode", JsValue::NULL).await {
+                Ok(saved_mode) => set_sort_mode.set(saved_mode),
+                Err(e) => show_error(e),
+            }
+
+            match invoke_cmd::<u32>("get_active_list", JsValue::NULL).await {
+                Ok(id) => set_active_list.set(id),
+                Err(e) => show_error(e),
+            }
+            reload_lists().await;
+
+            reload_todos().await;
             
             // Load and apply window state
             let window_state: Option<WindowState> =
-                serde_wasm_bindgen::from_value(invoke("load_window_state", JsValue::NULL).await)
-                    .ok()
-                    .flatten();
+                match invoke_cmd("load_window_state", JsValue::NULL).await {
+                    Ok(state) => state,
+                    Err(e) => {
+                        show_error(e);
+                        None
+                    }
+                };
             
             if let Some(state) = window_state {
                 // Apply window size and position
@@ -198,7 +898,7 @@ pub fn App() -> impl IntoView {
                     let args = serde_wasm_bindgen::to_value(&SetAlwaysOnTopArgs {
                         always_on_top: true,
                     }).unwrap();
-                    invoke("set_always_on_top", args).await;
+                    let _ = invoke("set_always_on_top", args).await;
                 }
             }
         });
@@ -211,7 +911,7 @@ pub fn App() -> impl IntoView {
                 always_on_top: new_pinned,
             })
             .unwrap();
-            invoke("set_always_on_top", args).await;
+            let _ = invoke("set_always_on_top", args).await;
             set_pinned.set(new_pinned);
             
             // Save window state
@@ -228,21 +928,83 @@ pub fn App() -> impl IntoView {
                 y,
                 pinned: new_pinned,
             }).unwrap();
-            invoke("save_window_state", save_args).await;
+            if let Err(e) = invoke_cmd::<()>("save_window_state", save_args).await {
+                show_error(e);
+            }
         });
     };
 
     let close = move |_| {
         spawn_local(async move {
-            invoke("close_window", JsValue::NULL).await;
+            flush_note().await;
+            let _ = invoke("close_window", JsValue::NULL).await;
         });
     };
 
-    /*
-    let toggle_edit = move |_| {
-        set_editing.update(|e| *e = !*e);
+    // Save what was typed when the window loses focus
+    Effect::new(move |_| {
+        let window = web_sys::window().unwrap();
+        let on_blur = Closure::<dyn FnMut()>::new(move || spawn_local(flush_note()));
+        let _ = window.add_event_listener_with_callback("blur", on_blur.as_ref().unchecked_ref());
+        on_blur.forget();
+    });
+
+    // The backend scheduler reset items while the window was open
+    Effect::new(move |_| {
+        let on_changed = Closure::<dyn FnMut(JsValue)>::new(move |_| spawn_local(reload_todos()));
+        spawn_local(async move {
+            listen("todos-changed", &on_changed).await;
+            on_changed.forget();
+        });
+    });
+
+    // Ctrl+Z / Ctrl+Shift+Z (or Ctrl+Y) undo and redo todo changes. Text
+    // fields keep their native undo.
+    Effect::new(move |_| {
+        let window = web_sys::window().unwrap();
+        let on_keydown = Closure::<dyn FnMut(_)>::new(move |ev: web_sys::KeyboardEvent| {
+            if !(ev.ctrl_key() || ev.meta_key()) {
+                return;
+            }
+            let in_text_field = ev.target().is_some_and(|t| {
+                t.dyn_ref::<web_sys::HtmlTextAreaElement>().is_some()
+                    || t.dyn_ref::<web_sys::HtmlInputElement>().is_some_and(|i| i.type_() != "checkbox")
+            });
+            if in_text_field {
+                return;
+            }
+            let key = ev.key().to_lowercase();
+            let cmd = match (key.as_str(), ev.shift_key()) {
+                ("z", false) => "undo",
+                ("z", true) | ("y", false) => "redo",
+                _ => return,
+            };
+            ev.prevent_default();
+            spawn_local(async move {
+                match invoke_cmd::<Option<String>>(cmd, JsValue::NULL).await {
+                    Ok(Some(label)) => {
+                        log(format!("↩️ {}: {}", cmd, label));
+                        reload_lists().await;
+                        reload_todos().await;
+                    }
+                    Ok(None) => {}
+                    Err(e) => show_error(e),
+                }
+            });
+        });
+        let _ = window.add_event_listener_with_callback("keydown", on_keydown.as_ref().unchecked_ref());
+        on_keydown.forget();
+    });
+
+    let select_note_view = move |view: NoteView| {
+        set_note_view.set(view);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&NoteViewArgs { view }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_note_view", args).await {
+                show_error(e);
+            }
+        });
     };
-    */
 
     // Global mouseup handler for drag and drop
     Effect::new(move |_| {
@@ -365,25 +1127,18 @@ pub fn App() -> impl IntoView {
                                 web_sys::console::log_1(&JsValue::from_str(&format!("[JS] Calling move_todo_item with id={}, parent={:?}, pos={}", dragged_id, final_parent, final_pos)));
                                 
                                 // Call backend with error handling
-                                let result = invoke("move_todo_item", args).await;
-                                
-                                web_sys::console::log_2(&JsValue::from_str("[JS] invoke returned:"), &result);
-                                
-                                // Check if there was an error
-                                if result.is_undefined() || result.is_null() {
-                                    log_async(format!("✅ Backend call complete (void return)"));
-                                } else {
-                                    log_async(format!("✅ Backend call complete: {:?}", result));
-                                }
+                                match invoke_cmd::<()>("move_todo_item", args).await {
+                                    Ok(()) => log_async("✅ Backend call complete".to_string()),
+                                    Err(e) => {
+                                        log_async(format!("❌ move_todo_item failed: {}", e.message));
+                                        show_error(e);
+                                    }
+                                }
                                 
                                 // Reload todos
-                                log_async(format!("🔄 Reloading todos..."));
-                                let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                                    invoke("load_todos", JsValue::NULL).await
-                                ).unwrap_or_default();
-                                let count = saved_todos.len();
-                                set_todos.set(saved_todos);
-                                log_async(format!("✅ Todos reloaded, count: {}", count));
+                                log_async("🔄 Reloading todos...".to_string());
+                                reload_todos().await;
+                                log_async(format!("✅ Todos reloaded, count: {}", todos.get_untracked().len()));
                             });
 
                     } else {
@@ -404,21 +1159,81 @@ pub fn App() -> impl IntoView {
         on_mouseup.forget();
     });
 
-    /*
-    let toggle_mode = move |_| {
-        set_mode.update(|m| *m = if *m == "note" { "todo" } else { "note" });
+    // Side views (trash, stats, ...) belong to the todos tab
+    let select_tab = move |tab: Tab| {
+        set_mode.set(if tab == Tab::Notes { "note" } else { "todo" });
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&TabArgs { tab }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_tab", args).await {
+                show_error(e);
+            }
+        });
     };
-    */
 
+    // Keeps a local draft at once and saves once typing pauses
     let update_note = move |ev| {
         let val = event_target_value(&ev);
         set_content.set(val.clone());
+        let id = active_note.get_untracked();
+        write_draft(id, &val);
+        pending_note.set_value(Some((id, val)));
+        set_save_status.set(SaveStatus::Pending);
+
+        let generation = save_generation.get_value() + 1;
+        save_generation.set_value(generation);
+        set_timeout(
+            move || {
+                if save_generation.get_value() == generation {
+                    spawn_local(flush_note());
+                }
+            },
+            NOTE_SAVE_DELAY,
+        );
+    };
+
+    // Puts back the saved text a recovered draft replaced
+    let discard_recovered = move |_| {
+        let Some((id, saved)) = recovered_note.get_untracked() else {
+            return;
+        };
+        set_recovered_note.set(None);
+        if active_note.get_untracked() == id {
+            set_content.set(saved.clone());
+        }
+        pending_note.set_value(Some((id, saved)));
+        spawn_local(flush_note());
+    };
+
+    let import_markdown = move |markdown: String| {
         spawn_local(async move {
-            let args = serde_wasm_bindgen::to_value(&SaveNoteArgs { content: val }).unwrap();
-            invoke("save_note_content", args).await;
+            let args = serde_wasm_bindgen::to_value(&ImportMarkdownArgs {
+                list_id: active_list.get_untracked(),
+                parent_id: None,
+                markdown,
+            })
+            .unwrap();
+            match invoke_cmd::<Vec<u32>>("import_markdown", args).await {
+                Ok(_) => reload_todos().await,
+                Err(e) => show_error(e),
+            }
         });
     };
 
+    // A pasted outline becomes a subtree instead of one flattened line
+    let paste_todo = move |ev: web_sys::Event| {
+        let Some(text) = ev
+            .dyn_ref::<web_sys::ClipboardEvent>()
+            .and_then(|ev| ev.clipboard_data())
+            .and_then(|data| data.get_data("text/plain").ok())
+        else {
+            return;
+        };
+        if text.trim().contains('\n') {
+            ev.prevent_default();
+            import_markdown(text);
+        }
+    };
+
     let add_todo = move |ev: SubmitEvent| {
         ev.prevent_default();
         let target = ev.target().unwrap();
@@ -430,25 +1245,20 @@ pub fn App() -> impl IntoView {
             .dyn_into::<web_sys::HtmlInputElement>()
             .unwrap();
         let text = input.value();
-        if !text.is_empty() {
+        if is_checklist(&text) {
+            import_markdown(text);
+            input.set_value("");
+        } else if !text.is_empty() {
             spawn_local(async move {
-                let args =
-                    serde_wasm_bindgen::to_value(&AddTodoArgs { text: text.clone() }).unwrap();
-                let id: u32 = serde_wasm_bindgen::from_value(invoke("add_todo_item", args).await)
-                    .unwrap_or(0);
-
-                if id != 0 {
-                    set_todos.update(|t| {
-                        t.push(TodoItem {
-                            id,
-                            text: text.clone(),
-                            completed: false,
-                            parent_id: None,
-                            position: 0,
-                            target_count: None,
-                            current_count: 0,
-                        })
-                    });
+                let args = serde_wasm_bindgen::to_value(&AddTodoArgs {
+                    list_id: active_list.get_untracked(),
+                    text: text.clone(),
+                })
+                .unwrap();
+                match invoke_cmd::<u32>("add_todo_item", args).await {
+                    // Reload rather than push, the backend strips `!priority` markers
+                    Ok(_) => reload_todos().await,
+                    Err(e) => show_error(e),
                 }
             });
             input.set_value("");
@@ -456,7 +1266,8 @@ pub fn App() -> impl IntoView {
     };
 
     let toggle_todo = move |id: u32| {
-        // Optimistic update
+        // Optimistic update, rolled back if the backend rejects it
+        let previous = todos.get_untracked();
         set_todos.update(|t| {
             if let Some(item) = t.iter_mut().find(|i| i.id == id) {
                 item.completed = !item.completed;
@@ -470,23 +1281,52 @@ pub fn App() -> impl IntoView {
             log(format!("🔄 Toggling todo {} to {}", id, completed));
 
             let args = serde_wasm_bindgen::to_value(&UpdateTodoArgs { id, completed }).unwrap();
-            invoke("update_todo_status", args).await;
+            if let Err(e) = invoke_cmd::<()>("update_todo_status", args).await {
+                set_todos.set(previous);
+                show_error(e);
+                return;
+            }
             
             // Reload todos to get cascading updates
-            log(format!("🔄 Reloading todos after toggle..."));
-            let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                invoke("load_todos", JsValue::NULL).await
-            ).unwrap_or_default();
-            set_todos.set(saved_todos);
-            log(format!("✅ Todos reloaded after toggle"));
+            log("🔄 Reloading todos after toggle...".to_string());
+            reload_todos().await;
+            log("✅ Todos reloaded after toggle".to_string());
+        });
+    };
+
+    // Shift+click on "×" keeps the children and moves them up one level
+    let delete_todo = move |id: u32, keep_children: bool| {
+        let previous = todos.get_untracked();
+        set_todos.update(|t| {
+            if keep_children {
+                t.retain(|i| i.id != id);
+            } else {
+                let mut removed = vec![id];
+                while let Some(next) = t.iter().find(|i| i.parent_id.is_some_and(|p| removed.contains(&p)) && !removed.contains(&i.id)) {
+                    removed.push(next.id);
+                }
+                t.retain(|i| !removed.contains(&i.id));
+            }
+        });
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&RemoveTodoArgs { id, keep_children }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("remove_todo_item", args).await {
+                set_todos.set(previous);
+                show_error(e);
+                return;
+            }
+            // Reload todos to pick up re-parented children and compacted positions
+            reload_todos().await;
         });
     };
 
-    let delete_todo = move |id: u32| {
-        set_todos.update(|t| t.retain(|i| i.id != id));
+    let archive_todo = move |id: u32| {
         spawn_local(async move {
-            let args = serde_wasm_bindgen::to_value(&RemoveTodoArgs { id }).unwrap();
-            invoke("remove_todo_item", args).await;
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("archive_todo_item", args).await {
+                show_error(e);
+            }
+            reload_todos().await;
         });
     };
 
@@ -496,52 +1336,86 @@ pub fn App() -> impl IntoView {
         }
         if ev.buttons() == 1 {
             spawn_local(async move {
-                invoke("start_drag", JsValue::NULL).await;
+                let _ = invoke("start_drag", JsValue::NULL).await;
             });
         }
     };
 
-    let render_markdown = move || {
-        let markdown_input = content.get();
-        let parser = pulldown_cmark::Parser::new(&markdown_input);
-        let mut html_output = String::new();
-        pulldown_cmark::html::push_html(&mut html_output, parser);
-        html_output
-    };
+    let render_markdown = move || render_block_markdown(&content.get());
 
     let set_todo_count = move |id: u32, count: Option<i32>| {
         spawn_local(async move {
             let args = serde_wasm_bindgen::to_value(&SetTodoCountArgs { id, count }).unwrap();
-            invoke("set_todo_count", args).await;
+            if let Err(e) = invoke_cmd::<()>("set_todo_count", args).await {
+                show_error(e);
+            }
             // Reload todos
-            let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                invoke("load_todos", JsValue::NULL).await
-            ).unwrap_or_default();
-            set_todos.set(saved_todos);
+            reload_todos().await;
+        });
+    };
+
+    let set_todo_dates = move |id: u32, due_at: Option<String>, scheduled_for: Option<String>| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetTodoDatesArgs { id, due_at, scheduled_for }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_todo_dates", args).await {
+                show_error(e);
+            }
+            reload_todos().await;
+        });
+    };
+
+    let set_todo_recurrence = move |id: u32, rule: Option<RecurrenceRule>| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetTodoRecurrenceArgs { id, rule }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_todo_recurrence", args).await {
+                show_error(e);
+            }
+            reload_todos().await;
+        });
+    };
+
+    let set_todo_priority = move |id: u32, priority: Priority| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetTodoPriorityArgs { id, priority }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_todo_priority", args).await {
+                show_error(e);
+            }
+            reload_todos().await;
+        });
+    };
+
+    let toggle_sort_mode = move |_| {
+        let new_mode = if sort_mode.get_untracked() == "priority" { "manual" } else { "priority" };
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetSortModeArgs { mode: new_mode.to_string() }).unwrap();
+            match invoke_cmd::<()>("set_sort_mode", args).await {
+                Ok(()) => set_sort_mode.set(new_mode.to_string()),
+                Err(e) => show_error(e),
+            }
+            reload_todos().await;
         });
     };
 
     let decrement_todo = move |id: u32| {
         spawn_local(async move {
             let args = serde_wasm_bindgen::to_value(&DecrementTodoArgs { id }).unwrap();
-            invoke("decrement_todo", args).await;
+            if let Err(e) = invoke_cmd::<()>("decrement_todo", args).await {
+                show_error(e);
+            }
             // Reload todos
-            let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                invoke("load_todos", JsValue::NULL).await
-            ).unwrap_or_default();
-            set_todos.set(saved_todos);
+            reload_todos().await;
         });
     };
 
     let reset_all_todos = move |_| {
         spawn_local(async move {
-            // Call backend to reset all todos
-            invoke("reset_all_todos", JsValue::NULL).await;
+            // Call backend to reset all todos of the current list
+            let args = serde_wasm_bindgen::to_value(&ListIdArgs { list_id: active_list.get_untracked() }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("reset_all_todos", args).await {
+                show_error(e);
+            }
             // Reload todos
-            let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                invoke("load_todos", JsValue::NULL).await
-            ).unwrap_or_default();
-            set_todos.set(saved_todos);
+            reload_todos().await;
         });
     };
 
@@ -550,12 +1424,21 @@ pub fn App() -> impl IntoView {
     let update_todo_text = move |id: u32, text: String| {
         spawn_local(async move {
             let args = serde_wasm_bindgen::to_value(&UpdateTodoTextArgs { id, text }).unwrap();
-            invoke("update_todo_text", args).await;
+            if let Err(e) = invoke_cmd::<()>("update_todo_text", args).await {
+                show_error(e);
+            }
             // Reload todos
-            let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                invoke("load_todos", JsValue::NULL).await
-            ).unwrap_or_default();
-            set_todos.set(saved_todos);
+            reload_todos().await;
+        });
+    };
+
+    let set_todo_description = move |id: u32, description: Option<String>| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetTodoDescriptionArgs { id, description }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_todo_description", args).await {
+                show_error(e);
+            }
+            reload_todos().await;
         });
     };
 
@@ -565,38 +1448,152 @@ pub fn App() -> impl IntoView {
                 class="h-8 bg-yellow-200 flex justify-between items-center px-2 cursor-move select-none"
                 on:mousedown=start_drag
             >
-                <span class="text-xs text-yellow-800 font-bold pointer-events-none">"TodoList"</span>
+                {move || window_note.get().is_none().then(|| view! {
+                    <div class="flex shrink-0 rounded bg-yellow-300/50" on:mousedown=move |ev| ev.stop_propagation()>
+                        <button
+                            on:click=move |_| select_tab(Tab::Todos)
+                            class=move || format!("px-1 py-0.5 text-xs rounded {}", if mode.get() != "note" { "bg-yellow-100 shadow-sm" } else { "opacity-60 hover:opacity-100" })
+                            title="Todos"
+                        >
+                            "✅"
+                        </button>
+                        <button
+                            on:click=move |_| select_tab(Tab::Notes)
+                            class=move || format!("px-1 py-0.5 text-xs rounded {}", if mode.get() == "note" { "bg-yellow-100 shadow-sm" } else { "opacity-60 hover:opacity-100" })
+                            title="Notes"
+                        >
+                            "📝"
+                        </button>
+                    </div>
+                })}
+                {move || if let Some(id) = window_note.get() {
+                    let title = move || notes.get().into_iter().find(|n| n.id == id).map(|n| n.title).unwrap_or_default();
+                    view! {
+                        <span class="text-xs text-yellow-800 font-bold px-1 max-w-[10rem] truncate">{title}</span>
+                    }.into_any()
+                } else if mode.get() == "note" {
+                    view! {
+                        <NoteSwitcher
+                            notes=notes
+                            active_note=active_note
+                            on_select=select_note
+                            on_changed=move || spawn_local(reload_notes())
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else {
+                    view! {
+                        <ListSwitcher
+                            lists=lists
+                            active_list=active_list
+                            on_select=select_list
+                            on_changed=move || spawn_local(async move {
+                                reload_lists().await;
+                                reload_todos().await;
+                            })
+                            show_error=show_error
+                        />
+                    }.into_any()
+                }}
+                {move || window_note.get().is_none().then(|| view! {
+                    <SearchBox on_select=jump_to show_error=show_error />
+                })}
                 <div class="flex gap-1">
-                    <button
-                        on:click=reset_all_todos
-                        on:mousedown=move |ev| ev.stop_propagation()
-                        class="px-2 py-0.5 text-xs rounded hover:bg-yellow-300 text-yellow-700 transition-colors"
-                        title="Reset all items to incomplete"
-                    >
-                        "↻"
-                    </button>
-                    /*
-                    <button
-                        on:click=toggle_mode
-                        on:mousedown=move |ev| ev.stop_propagation()
-                        class="p-1 rounded hover:bg-yellow-300 text-yellow-600 transition-colors text-xs"
-                    >
-                        {move || if mode.get() == "note" { "📝" } else { "✅" }}
-                    </button>
-                    {move || if mode.get() == "note" {
-                        view! {
-                            <button
-                                on:click=toggle_edit
-                                on:mousedown=move |ev| ev.stop_propagation()
-                                class="p-1 rounded hover:bg-yellow-300 text-yellow-600 transition-colors"
-                            >
-                                {move || if editing.get() { "👁️" } else { "✏️" }}
-                            </button>
-                        }.into_any()
-                    } else {
-                        view! { <span class="w-6"></span> }.into_any()
-                    }}
-                    */
+                    {move || (mode.get() == "note").then(|| view! {
+                        <div class="flex rounded bg-yellow-300/50">
+                            {NoteView::ALL.into_iter().map(|view| view! {
+                                <button
+                                    on:click=move |_| {
+                                        set_show_revisions.set(false);
+                                        select_note_view(view);
+                                    }
+                                    on:mousedown=move |ev| ev.stop_propagation()
+                                    class=move || format!("px-1 py-0.5 text-xs rounded {}", if note_view.get() == view && !show_revisions.get() { "bg-yellow-100 shadow-sm" } else { "opacity-60 hover:opacity-100" })
+                                    title=view.title()
+                                >
+                                    {view.icon()}
+                                </button>
+                            }).collect_view()}
+                        </div>
+                        <span class=move || format!("self-center text-[10px] {}", save_status.get().class())>
+                            {move || save_status.get().label()}
+                        </span>
+                        <button
+                            on:click=move |_| set_show_revisions.update(|s| *s = !*s)
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if show_revisions.get() { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Revision history"
+                        >
+                            "🕘"
+                        </button>
+                    })}
+                    // Todo tools, also hidden in a note's own window
+                    <div class=move || if window_note.get().is_some() || mode.get() == "note" { "hidden" } else { "contents" }>
+                        <button
+                            on:click=reset_all_todos
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class="px-2 py-0.5 text-xs rounded hover:bg-yellow-300 text-yellow-700 transition-colors"
+                            title="Reset all items in this list to incomplete"
+                        >
+                            "↻"
+                        </button>
+                        <button
+                            on:click=move |_| set_mode.update(|m| *m = if *m == "schedules" { "todo" } else { "schedules" })
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if mode.get() == "schedules" { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Automatic resets"
+                        >
+                            "⏰"
+                        </button>
+                        <button
+                            on:click=move |_| set_mode.update(|m| *m = if *m == "stats" { "todo" } else { "stats" })
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if mode.get() == "stats" { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Statistics"
+                        >
+                            "📊"
+                        </button>
+                        <button
+                            on:click=move |_| set_mode.update(|m| *m = if *m == "views" { "todo" } else { "views" })
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if mode.get() == "views" || active_view.get().is_some() { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Saved views"
+                        >
+                            "🔖"
+                        </button>
+                        <button
+                            on:click=toggle_sort_mode
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if sort_mode.get() == "priority" { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title=move || if sort_mode.get() == "priority" { "Sorted by priority (click for manual order)" } else { "Manual order (click to sort by priority)" }
+                        >
+                            "⇅"
+                        </button>
+                        <button
+                            on:click=move |_| set_mode.update(|m| *m = if *m == "archive" { "todo" } else { "archive" })
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if mode.get() == "archive" { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Archive"
+                        >
+                            "📦"
+                        </button>
+                        <button
+                            on:click=move |_| set_mode.update(|m| *m = if *m == "trash" { "todo" } else { "trash" })
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if mode.get() == "trash" { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Trash"
+                        >
+                            "🗑"
+                        </button>
+                        <button
+                            on:click=move |_| set_mode.update(|m| *m = if *m == "backup" { "todo" } else { "backup" })
+                            on:mousedown=move |ev| ev.stop_propagation()
+                            class=move || format!("px-1 py-0.5 text-xs rounded hover:bg-yellow-300 transition-colors {}", if mode.get() == "backup" { "bg-yellow-300 text-yellow-800" } else { "text-yellow-700" })
+                            title="Backup and restore"
+                        >
+                            "💾"
+                        </button>
+                    </div>
                     <button
                         on:click=toggle_pin
                         on:mousedown=move |ev| ev.stop_propagation()
@@ -614,29 +1611,127 @@ pub fn App() -> impl IntoView {
                 </div>
             </div>
 
+            {move || startup_error.get().map(|msg| view! {
+                <div class="bg-red-100 border-b border-red-300 text-red-700 text-xs px-2 py-1">
+                    {msg}
+                </div>
+            })}
+
             <div class="flex-1 p-2 overflow-auto">
-                {move || if mode.get() == "note" {
-                    if editing.get() {
-                        view! {
-                            <textarea
-                                class="w-full h-full bg-transparent resize-none outline-none text-gray-800 font-sans text-sm"
-                                placeholder="Type your note here..."
-                                on:input=update_note
-                                prop:value=content
-                            ></textarea>
-                        }.into_any()
-                    } else {
-                        view! {
-                            <div
-                                class="prose prose-sm max-w-none text-gray-800 prose-p:my-1 prose-headings:my-2"
-                                inner_html=render_markdown()
-                            ></div>
-                        }.into_any()
+                {move || if mode.get() == "trash" {
+                    view! {
+                        <TrashView
+                            on_restored=move || spawn_local(reload_todos())
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else if mode.get() == "archive" {
+                    view! {
+                        <ArchiveView
+                            on_restored=move || spawn_local(reload_todos())
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else if mode.get() == "schedules" {
+                    view! {
+                        <ResetSchedulesView
+                            list_id=active_list.get()
+                            todos=todos
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else if mode.get() == "stats" {
+                    view! { <StatsView show_error=show_error /> }.into_any()
+                } else if mode.get() == "backup" {
+                    view! {
+                        <BackupView
+                            on_restored=move || spawn_local(async move {
+                                reload_lists().await;
+                                reload_todos().await;
+                                reload_notes().await;
+                            })
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else if mode.get() == "views" {
+                    view! {
+                        <SavedViewsView
+                            active_view=active_view
+                            on_open=open_view
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else if mode.get() == "note" && show_revisions.get() {
+                    view! {
+                        <RevisionsView
+                            note_id=active_note.get()
+                            on_restored=move || {
+                                set_show_revisions.set(false);
+                                select_note(active_note.get_untracked());
+                            }
+                            show_error=show_error
+                        />
+                    }.into_any()
+                } else if mode.get() == "note" {
+                    let editor = move || view! {
+                        <textarea
+                            class="flex-1 min-w-0 h-full bg-transparent resize-none outline-none text-gray-800 font-sans text-sm"
+                            placeholder="Type your note here..."
+                            on:input=update_note
+                            on:blur=move |_| spawn_local(flush_note())
+                            prop:value=content
+                        ></textarea>
+                    };
+                    let preview = move || view! {
+                        <div
+                            class="flex-1 min-w-0 h-full overflow-auto prose prose-sm max-w-none text-gray-800 prose-p:my-1 prose-headings:my-2"
+                            inner_html=render_markdown
+                        ></div>
+                    };
+                    let panes = match note_view.get() {
+                        NoteView::Edit => view! { <div class="flex flex-1 min-h-0">{editor()}</div> }.into_any(),
+                        NoteView::Preview => view! { <div class="flex flex-1 min-h-0">{preview()}</div> }.into_any(),
+                        NoteView::Split => view! {
+                            <div class="flex flex-1 min-h-0 gap-2">
+                                {editor()}
+                                <div class="w-px bg-yellow-300"></div>
+                                {preview()}
+                            </div>
+                        }.into_any(),
+                    };
+                    view! {
+                        <div class="flex flex-col h-full">
+                            {move || recovered_note.get().is_some().then(|| view! {
+                                <div class="flex items-center gap-2 mb-2 px-2 py-1 rounded bg-amber-50 border border-amber-300 text-xs text-amber-800">
+                                    <span class="flex-1">"Recovered changes that were not saved"</span>
+                                    <button class="hover:text-amber-950" on:click=move |_| set_recovered_note.set(None)>"Keep"</button>
+                                    <button class="hover:text-amber-950" on:click=discard_recovered>"Discard"</button>
+                                </div>
+                            })}
+                            {panes}
+                        </div>
                     }.into_any()
                 } else {
                     view! {
                         <div class="flex flex-col h-full">
-                            <form on:submit=add_todo class="flex gap-2 mb-2 relative">
+                            {move || active_view.get().map(|saved_view| view! {
+                                <div class="flex items-center gap-2 mb-2 px-2 py-1 rounded bg-indigo-50 border border-indigo-200 text-xs">
+                                    <span class="font-semibold text-indigo-800">{saved_view.name}</span>
+                                    <code class="flex-1 truncate text-indigo-500">{saved_view.query}</code>
+                                    <button
+                                        class="text-indigo-400 hover:text-indigo-700"
+                                        title="Back to the list"
+                                        on:click=move |_| open_view(None)
+                                    >
+                                        "×"
+                                    </button>
+                                </div>
+                            })}
+                            // Items are added to lists, not to views
+                            <form
+                                on:submit=add_todo
+                                class=move || format!("flex gap-2 mb-2 relative {}", if active_view.get().is_some() { "hidden" } else { "" })
+                            >
                                 <div 
                                     class=move || format!(
                                         "absolute top-full left-0 mt-1 w-64 bg-white backdrop-blur-sm rounded-lg shadow-xl border border-gray-200 p-3 text-xs z-[9999] transition-all duration-200 transform origin-top {}",
@@ -668,6 +1763,14 @@ pub fn App() -> impl IntoView {
                                         <code class="bg-gray-100 px-1 rounded text-gray-600">"*b*Blue*b*"</code>
                                         <span class="text-gray-400">"→"</span>
                                         <span class="text-blue-500">"Blue"</span>
+
+                                        <code class="bg-gray-100 px-1 rounded text-gray-600">"!high"</code>
+                                        <span class="text-gray-400">"→"</span>
+                                        <span class="text-orange-600 font-bold">"High priority"</span>
+
+                                        <code class="bg-gray-100 px-1 rounded text-gray-600">"#tag"</code>
+                                        <span class="text-gray-400">"→"</span>
+                                        <span><span class="inline-block px-1 rounded bg-indigo-100 text-indigo-700 text-xs">"#tag"</span></span>
                                     </div>
                                     <div class="absolute top-0 left-4 transform -translate-y-1/2 rotate-45 w-2 h-2 border-l border-t border-gray-200" style="background-color: rgba(255, 255, 255, 0.95);"></div>
                                 </div>
@@ -675,10 +1778,11 @@ pub fn App() -> impl IntoView {
                                 <input
                                     name="todo-input"
                                     class="flex-1 bg-white/50 border-none rounded px-2 py-1 text-sm outline-none focus:bg-white"
-                                    placeholder="Add todo... (Markdown supported)"
+                                    placeholder="Add todo... (Markdown, !high)"
                                     autocomplete="off"
                                     on:focus=move |_| set_show_markdown_tip.set(true)
                                     on:blur=move |_| set_show_markdown_tip.set(false)
+                                    on:paste=paste_todo
                                 />
                                 <button type="submit" class="text-green-600 hover:text-green-700 font-bold">"+"</button>
                             </form>
@@ -688,8 +1792,14 @@ pub fn App() -> impl IntoView {
                                     parent_id=None
                                     toggle_todo=toggle_todo
                                     delete_todo=delete_todo
+                                    archive_todo=archive_todo
+                                    set_todo_description=set_todo_description
                                     log=log
                                     on_drop=move |dragged_id, target_parent_id, target_pos| {
+                                        // Positions in a filtered view do not map to the real tree
+                                        if active_view.get_untracked().is_some() {
+                                            return;
+                                        }
                                         log(format!("Dropped {} -> {:?}", dragged_id, target_parent_id));
                                         spawn_local(async move {
                                             let args = serde_wasm_bindgen::to_value(&MoveTodoArgs {
@@ -697,11 +1807,10 @@ pub fn App() -> impl IntoView {
                                                 target_parent_id,
                                                 target_position: target_pos
                                             }).unwrap();
-                                            invoke("move_todo_item", args).await;
-                                            let saved_todos: Vec<TodoItem> = serde_wasm_bindgen::from_value(
-                                                invoke("load_todos", JsValue::NULL).await
-                                            ).unwrap_or_default();
-                                            set_todos.set(saved_todos);
+                                            if let Err(e) = invoke_cmd::<()>("move_todo_item", args).await {
+                                                show_error(e);
+                                            }
+                                            reload_todos().await;
                                         });
                                     }
                                     dragging_id=dragging_id
@@ -711,9 +1820,13 @@ pub fn App() -> impl IntoView {
                                     drop_position=drop_position
                                     set_drop_position=set_drop_position
                                     set_todo_count=set_todo_count
+                                    set_todo_dates=set_todo_dates
+                                    set_todo_priority=set_todo_priority
+                                    set_todo_recurrence=set_todo_recurrence
                                     decrement_todo=decrement_todo
                                     editing_todo_id=editing_todo_id
                                     set_editing_todo_id=set_editing_todo_id
+                                    highlighted_id=highlighted_id
                                     update_todo_text=update_todo_text
                                 />
 
@@ -722,6 +1835,15 @@ pub fn App() -> impl IntoView {
                     }.into_any()
                 }}
             </div>
+
+            {move || error_toast.get().map(|(_, msg)| view! {
+                <div
+                    class="fixed bottom-2 left-2 right-2 bg-red-500/90 text-white text-xs rounded shadow-lg px-2 py-1 cursor-pointer z-[9999]"
+                    on:click=move |_| set_error_toast.set(None)
+                >
+                    {msg}
+                </div>
+            })}
         </main>
     }
 }
@@ -732,12 +1854,20 @@ struct UpdateTodoTextArgs {
     text: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct SetTodoDescriptionArgs {
+    id: u32,
+    description: Option<String>,
+}
+
 #[component]
-fn TodoList<F1, F2, F3, F4, F5, F6, F7>(
+fn TodoList<F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12>(
     todos: Signal<Vec<TodoItem>>,
     parent_id: Option<u32>,
     toggle_todo: F1,
     delete_todo: F2,
+    archive_todo: F11,
+    set_todo_description: F12,
     log: F4,
     on_drop: F3,
     dragging_id: ReadSignal<Option<u32>>,
@@ -747,19 +1877,28 @@ fn TodoList<F1, F2, F3, F4, F5, F6, F7>(
     drop_position: ReadSignal<f64>,
     set_drop_position: WriteSignal<f64>,
     set_todo_count: F5,
+    set_todo_dates: F8,
+    set_todo_priority: F9,
+    set_todo_recurrence: F10,
     decrement_todo: F6,
     editing_todo_id: ReadSignal<Option<u32>>,
     set_editing_todo_id: WriteSignal<Option<u32>>,
+    highlighted_id: ReadSignal<Option<u32>>,
     update_todo_text: F7,
 ) -> impl IntoView
 where
     F1: Fn(u32) + Clone + Send + 'static,
-    F2: Fn(u32) + Clone + Send + 'static,
+    F2: Fn(u32, bool) + Clone + Send + 'static,
     F3: Fn(u32, Option<u32>, i32) + Clone + Send + 'static,
     F4: Fn(String) + Clone + Send + 'static,
     F5: Fn(u32, Option<i32>) + Clone + Send + 'static,
     F6: Fn(u32) + Clone + Send + 'static,
     F7: Fn(u32, String) + Clone + Send + 'static,
+    F8: Fn(u32, Option<String>, Option<String>) + Clone + Send + 'static,
+    F9: Fn(u32, Priority) + Clone + Send + 'static,
+    F10: Fn(u32, Option<RecurrenceRule>) + Clone + Send + 'static,
+    F11: Fn(u32) + Clone + Send + 'static,
+    F12: Fn(u32, Option<String>) + Clone + Send + 'static,
 {
 
     view! {
@@ -779,6 +1918,8 @@ where
                             all_todos=todos
                             toggle_todo=toggle_todo.clone() 
                             delete_todo=delete_todo.clone() 
+                            archive_todo=archive_todo.clone()
+                            set_todo_description=set_todo_description.clone()
                             log=log.clone() 
                             on_drop=on_drop.clone()
                             dragging_id=dragging_id
@@ -788,9 +1929,13 @@ where
                             drop_position=drop_position
                             set_drop_position=set_drop_position
                             set_todo_count=set_todo_count.clone()
+                            set_todo_dates=set_todo_dates.clone()
+                            set_todo_priority=set_todo_priority.clone()
+                            set_todo_recurrence=set_todo_recurrence.clone()
                             decrement_todo=decrement_todo.clone()
                             editing_todo_id=editing_todo_id
                             set_editing_todo_id=set_editing_todo_id
+                            highlighted_id=highlighted_id
                             update_todo_text=update_todo_text.clone()
                         />
                     }
@@ -801,11 +1946,13 @@ where
 }
 
 #[component]
-fn TodoItemView<F1, F2, F3, F4, F5, F6, F7>(
+fn TodoItemView<F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12>(
     todo: TodoItem,
     all_todos: Signal<Vec<TodoItem>>,
     toggle_todo: F1,
     delete_todo: F2,
+    archive_todo: F11,
+    set_todo_description: F12,
     log: F4,
     on_drop: F3,
     dragging_id: ReadSignal<Option<u32>>,
@@ -815,19 +1962,28 @@ fn TodoItemView<F1, F2, F3, F4, F5, F6, F7>(
     drop_position: ReadSignal<f64>,
     set_drop_position: WriteSignal<f64>,
     set_todo_count: F5,
+    set_todo_dates: F8,
+    set_todo_priority: F9,
+    set_todo_recurrence: F10,
     decrement_todo: F6,
     editing_todo_id: ReadSignal<Option<u32>>,
     set_editing_todo_id: WriteSignal<Option<u32>>,
+    highlighted_id: ReadSignal<Option<u32>>,
     update_todo_text: F7,
 ) -> AnyView
 where
     F1: Fn(u32) + Clone + Send + 'static,
-    F2: Fn(u32) + Clone + Send + 'static,
+    F2: Fn(u32, bool) + Clone + Send + 'static,
     F3: Fn(u32, Option<u32>, i32) + Clone + Send + 'static,
     F4: Fn(String) + Clone + Send + 'static,
     F5: Fn(u32, Option<i32>) + Clone + Send + 'static,
     F6: Fn(u32) + Clone + Send + 'static,
     F7: Fn(u32, String) + Clone + Send + 'static,
+    F8: Fn(u32, Option<String>, Option<String>) + Clone + Send + 'static,
+    F9: Fn(u32, Priority) + Clone + Send + 'static,
+    F10: Fn(u32, Option<RecurrenceRule>) + Clone + Send + 'static,
+    F11: Fn(u32) + Clone + Send + 'static,
+    F12: Fn(u32, Option<String>) + Clone + Send + 'static,
 {
     let id = todo.id;
     
@@ -899,6 +2055,18 @@ where
 
         if dragging_id.get() == Some(id) {
             classes.push("opacity-50 scale-95 ring-2 ring-blue-400".to_string());
+        } else if highlighted_id.get() == Some(id) {
+            classes.push("ring-2 ring-indigo-400".to_string());
+        }
+
+        if current_todo.get().is_context {
+            classes.push("opacity-60".to_string());
+        }
+
+        match due_status(&current_todo.get()) {
+            Some(DueStatus::Overdue) => classes.push("border-l-4 border-l-red-400".to_string()),
+            Some(DueStatus::Today) => classes.push("border-l-4 border-l-amber-400".to_string()),
+            _ => {}
         }
 
         if drop_target_id.get() == Some(id) {
@@ -935,9 +2103,21 @@ where
         }
     };
 
+    let (show_details, set_show_details) = signal(false);
+    let (editing_description, set_editing_description) = signal(false);
+
+    let save_description = {
+        let set_todo_description = set_todo_description.clone();
+        move |text: String| {
+            set_todo_description(id, Some(text));
+            set_editing_description.set(false);
+        }
+    };
+
     view! {
         <li 
             class=item_class
+            data-todo-id=id
             on:mousedown=on_mousedown
             on:mouseenter=on_mouseenter
             on:mousemove=on_mousemove
@@ -984,10 +2164,13 @@ where
                     }
                 }}
 
-                {move || {
+                {
+                    let update_todo_text = update_todo_text.clone();
+                    move || {
                     if is_editing() {
                         let save_edit_blur = save_edit.clone();
                         let save_edit_keydown = save_edit.clone();
+                        let update_todo_text_blur = update_todo_text.clone();
                         view! {
                             <input
                                 type="text"
@@ -995,7 +2178,11 @@ where
                                 prop:value=current_todo.get().text
                                 on:blur=move |ev| {
                                     let input_element = event_target::<web_sys::HtmlInputElement>(&ev);
-                                    save_edit_blur(input_element.value());
+                                    if focus_stays_in_editor(&ev, id) {
+                                        update_todo_text_blur(id, input_element.value());
+                                    } else {
+                                        save_edit_blur(input_element.value());
+                                    }
                                 }
                                 on:keydown=move |ev| {
                                     if ev.key() == "Enter" {
@@ -1007,6 +2194,7 @@ where
                                 }
                                 on:mousedown=move |ev| ev.stop_propagation()
                                 on:click=move |ev| ev.stop_propagation()
+                                data-todo-editor=id
                                 autofocus
                             />
                         }.into_any()
@@ -1025,6 +2213,27 @@ where
                     }
                 }}
 
+                {move || date_badges(&current_todo.get())}
+
+                <select
+                    class=move || format!("text-xs bg-transparent rounded cursor-pointer outline-none {}", current_todo.get().priority.class())
+                    title="Priority"
+                    on:change={
+                        let set_priority = set_todo_priority.clone();
+                        move |ev| {
+                            let value = event_target_value(&ev);
+                            let priority = Priority::ALL.into_iter().find(|p| p.key() == value).unwrap_or_default();
+                            set_priority(id, priority);
+                        }
+                    }
+                    on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                    on:click=move |ev: MouseEvent| ev.stop_propagation()
+                >
+                    {Priority::ALL.into_iter().map(|p| view! {
+                        <option value=p.key() prop:selected=move || current_todo.get().priority == p>{p.label()}</option>
+                    }).collect_view()}
+                </select>
+
                 <input
                     type="number"
                     class="w-12 p-1 text-xs border rounded text-center text-gray-500"
@@ -1043,20 +2252,309 @@ where
                     on:click=move |ev: MouseEvent| ev.stop_propagation()
                 />
 
+                <button
+                    on:click=move |_| set_show_details.update(|open| *open = !*open)
+                    class=move || format!(
+                        "text-xs {}",
+                        if current_todo.get().description.is_some() { "text-blue-500 hover:text-blue-700" } else { "text-gray-300 hover:text-gray-500" }
+                    )
+                    title="Details"
+                    on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                >{move || if show_details.get() { "▾" } else { "▸" }}</button>
+
+                {
+                    let archive_todo = archive_todo.clone();
+                    move || current_todo.get().completed.then(|| {
+                        let archive = archive_todo.clone();
+                        view! {
+                            <button
+                                on:click=move |_| archive(id)
+                                class="text-gray-400 hover:text-gray-600 text-xs"
+                                title="Archive with sub-items"
+                                on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                            >"📦"</button>
+                        }
+                    })
+                }
+
                 <button 
                     on:click={
                         let del = delete_todo.clone();
-                        move |_| del(id)
+                        move |ev: MouseEvent| del(id, ev.shift_key())
                     }
                     class="text-red-400 hover:text-red-600 text-xs"
+                    title="Move to trash (Shift+click keeps sub-items)"
                     on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
                 >"×"</button>
             </div>
+            {
+                let save_description = save_description.clone();
+                move || show_details.get().then(|| {
+                    let save_blur = save_description.clone();
+                    let save_keydown = save_description.clone();
+                    view! {
+                        <div
+                            class="mt-1 ml-6 p-2 rounded bg-yellow-50 border border-yellow-200"
+                            on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                        >
+                            {move || if editing_description.get() {
+                                let save_blur = save_blur.clone();
+                                let save_keydown = save_keydown.clone();
+                                view! {
+                                    <textarea
+                                        class="w-full h-32 p-1 text-sm font-mono bg-white border border-yellow-300 rounded outline-none resize-y"
+                                        placeholder="Markdown... (Ctrl+Enter to save, Esc to cancel)"
+                                        prop:value=current_todo.get_untracked().description.unwrap_or_default()
+                                        on:blur=move |ev| {
+                                            let area = event_target::<web_sys::HtmlTextAreaElement>(&ev);
+                                            if editing_description.get_untracked() {
+                                                save_blur(area.value());
+                                            }
+                                        }
+                                        on:keydown=move |ev| {
+                                            if ev.key() == "Enter" && ev.ctrl_key() {
+                                                ev.prevent_default();
+                                                let area = event_target::<web_sys::HtmlTextAreaElement>(&ev);
+                                                save_keydown(area.value());
+                                            } else if ev.key() == "Escape" {
+                                                set_editing_description.set(false);
+                                            }
+                                        }
+                                        autofocus
+                                    ></textarea>
+                                }.into_any()
+                            } else {
+                                match current_todo.get().description {
+                                    Some(description) => view! {
+                                        <div
+                                            class="prose prose-sm max-w-none text-gray-800 prose-p:my-1 prose-headings:my-2 cursor-text"
+                                            inner_html=render_block_markdown(&description)
+                                            on:dblclick=move |_| set_editing_description.set(true)
+                                        ></div>
+                                    }.into_any(),
+                                    None => view! {
+                                        <div
+                                            class="text-xs text-gray-400 italic cursor-text"
+                                            on:click=move |_| set_editing_description.set(true)
+                                        >"No description, click to add"</div>
+                                    }.into_any(),
+                                }
+                            }}
+                        </div>
+                    }
+                })
+            }
+            {
+                let set_todo_dates = set_todo_dates.clone();
+                let set_todo_recurrence = set_todo_recurrence.clone();
+                move || is_editing().then(|| {
+                let t = current_todo.get_untracked();
+                let set_dates = set_todo_dates.clone();
+                let set_rule = {
+                    let set_todo_recurrence = set_todo_recurrence.clone();
+                    move |rule: Option<RecurrenceRule>| set_todo_recurrence(id, rule)
+                };
+                let rule = Memo::new(move |_| current_todo.get().recurrence);
+                let kind = Memo::new(move |_| rule.get().map(|r| r.repeat.kind()).unwrap_or("never"));
+                let with_repeat = {
+                    let set_rule = set_rule.clone();
+                    move |repeat: Repeat| {
+                        let mode = rule.get_untracked().map(|r| r.mode).unwrap_or_default();
+                        set_rule(Some(RecurrenceRule { repeat, mode }));
+                    }
+                };
+                let on_kind_change = {
+                    let set_rule = set_rule.clone();
+                    let with_repeat = with_repeat.clone();
+                    move |ev: web_sys::Event| {
+                        let now = js_sys::Date::new_0();
+                        match event_target_value(&ev).as_str() {
+                            "daily" => with_repeat(Repeat::Daily),
+                            "weekly" => with_repeat(Repeat::Weekly { weekdays: vec![((now.get_day() + 6) % 7) as u8] }),
+                            "monthly" => with_repeat(Repeat::Monthly { day: now.get_date() as u8 }),
+                            "after_completion" => with_repeat(Repeat::AfterCompletion { days: 1 }),
+                            _ => set_rule(None),
+                        }
+                    }
+                };
+                let on_date_change = move |ev: web_sys::Event| {
+                    let field = event_target::<web_sys::HtmlInputElement>(&ev);
+                    let value = Some(field.value()).filter(|v| !v.is_empty());
+                    let t = current_todo.get_untracked();
+                    if field.name() == "due_at" {
+                        set_dates(id, value, t.scheduled_for);
+                    } else {
+                        set_dates(id, t.due_at, value);
+                    }
+                };
+                let on_date_blur = move |ev: web_sys::FocusEvent| {
+                    if !focus_stays_in_editor(&ev, id) {
+                        set_editing_todo_id.set(None);
+                    }
+                };
+                view! {
+                    <div class="flex items-center gap-2 pl-6 pt-1 text-xs text-gray-500">
+                        <label class="flex items-center gap-1">
+                            "Due"
+                            <input
+                                type="date"
+                                name="due_at"
+                                class="border rounded px-1 bg-white"
+                                prop:value=t.due_at.unwrap_or_default()
+                                on:change=on_date_change.clone()
+                                on:blur=on_date_blur
+                                on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                                data-todo-editor=id
+                            />
+                        </label>
+                        <label class="flex items-center gap-1">
+                            "Scheduled"
+                            <input
+                                type="date"
+                                name="scheduled_for"
+                                class="border rounded px-1 bg-white"
+                                prop:value=t.scheduled_for.unwrap_or_default()
+                                on:change=on_date_change
+                                on:blur=on_date_blur
+                                on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                                data-todo-editor=id
+                            />
+                        </label>
+                    </div>
+                    <div class="flex flex-wrap items-center gap-2 pl-6 pt-1 text-xs text-gray-500">
+                        <label class="flex items-center gap-1">
+                            "Repeat"
+                            <select
+                                class="border rounded px-1 bg-white"
+                                on:change=on_kind_change
+                                on:blur=on_date_blur
+                                on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                                data-todo-editor=id
+                            >
+                                {[
+                                    ("never", "Never"),
+                                    ("daily", "Daily"),
+                                    ("weekly", "Weekly"),
+                                    ("monthly", "Monthly"),
+                                    ("after_completion", "After completion"),
+                                ].into_iter().map(|(value, label)| view! {
+                                    <option value=value prop:selected=move || kind.get() == value>{label}</option>
+                                }).collect_view()}
+                            </select>
+                        </label>
+                        {
+                            let with_repeat = with_repeat.clone();
+                            move || match rule.get().map(|r| r.repeat) {
+                                Some(Repeat::Weekly { weekdays }) => {
+                                    let with_repeat = with_repeat.clone();
+                                    WEEKDAY_LABELS.iter().enumerate().map(|(day, label)| {
+                                        let day = day as u8;
+                                        let selected = weekdays.contains(&day);
+                                        let weekdays = weekdays.clone();
+                                        let with_repeat = with_repeat.clone();
+                                        view! {
+                                            <button
+                                                class=format!("w-5 rounded {}", if selected { "bg-yellow-400 text-white" } else { "bg-gray-100" })
+                                                title=*label
+                                                // Keep focus where it is so the editor stays open
+                                                on:mousedown=move |ev: MouseEvent| {
+                                                    ev.prevent_default();
+                                                    ev.stop_propagation();
+                                                }
+                                                on:click=move |_| {
+                                                    let mut days: Vec<u8> = weekdays.iter().copied().filter(|d| *d != day).collect();
+                                                    if !selected {
+                                                        days.push(day);
+                                                        days.sort();
+                                                    }
+                                                    if !days.is_empty() {
+                                                        with_repeat(Repeat::Weekly { weekdays: days });
+                                                    }
+                                                }
+                                            >
+                                                {label.chars().next().unwrap_or_default().to_string()}
+                                            </button>
+                                        }
+                                    }).collect_view().into_any()
+                                }
+                                Some(Repeat::Monthly { day }) => {
+                                    let with_repeat = with_repeat.clone();
+                                    view! {
+                                        <label class="flex items-center gap-1">
+                                            "on day"
+                                            <input
+                                                type="number"
+                                                min="1"
+                                                max="31"
+                                                class="w-10 border rounded px-1 text-center"
+                                                prop:value=day.to_string()
+                                                on:change=move |ev| {
+                                                    if let Ok(day @ 1..=31) = event_target_value(&ev).parse::<u8>() {
+                                                        with_repeat(Repeat::Monthly { day });
+                                                    }
+                                                }
+                                                on:blur=on_date_blur
+                                                on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                                                data-todo-editor=id
+                                            />
+                                        </label>
+                                    }.into_any()
+                                }
+                                Some(Repeat::AfterCompletion { days }) => {
+                                    let with_repeat = with_repeat.clone();
+                                    view! {
+                                        <label class="flex items-center gap-1">
+                                            <input
+                                                type="number"
+                                                min="1"
+                                                max="3660"
+                                                class="w-10 border rounded px-1 text-center"
+                                                prop:value=days.to_string()
+                                                on:change=move |ev| {
+                                                    if let Ok(days @ 1..) = event_target_value(&ev).parse::<u32>() {
+                                                        with_repeat(Repeat::AfterCompletion { days });
+                                                    }
+                                                }
+                                                on:blur=on_date_blur
+                                                on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                                                data-todo-editor=id
+                                            />
+                                            "days later"
+                                        </label>
+                                    }.into_any()
+                                }
+                                _ => ().into_any(),
+                            }
+                        }
+                        {move || rule.get().map(|r| {
+                            let set_rule = set_rule.clone();
+                            view! {
+                                <select
+                                    class="border rounded px-1 bg-white"
+                                    title="What completing the item does"
+                                    on:change=move |ev| {
+                                        let mode = if event_target_value(&ev) == "spawn" { RecurrenceMode::Spawn } else { RecurrenceMode::Reschedule };
+                                        set_rule(Some(RecurrenceRule { repeat: r.repeat.clone(), mode }));
+                                    }
+                                    on:blur=on_date_blur
+                                    on:mousedown=move |ev: MouseEvent| ev.stop_propagation()
+                                    data-todo-editor=id
+                                >
+                                    <option value="reschedule" selected=r.mode == RecurrenceMode::Reschedule>"Reschedule"</option>
+                                    <option value="spawn" selected=r.mode == RecurrenceMode::Spawn>"New copy"</option>
+                                </select>
+                            }
+                        })}
+                    </div>
+                }
+            })}
             <TodoList 
                 todos=all_todos 
                 parent_id=Some(id) 
                 toggle_todo=toggle_todo 
                 delete_todo=delete_todo 
+                archive_todo=archive_todo
+                set_todo_description=set_todo_description
                 log=log 
                 on_drop=on_drop
                 dragging_id=dragging_id
@@ -1066,12 +2564,1797 @@ where
                 drop_position=drop_position
                 set_drop_position=set_drop_position
                 set_todo_count=set_todo_count
+                set_todo_dates=set_todo_dates
+                set_todo_priority=set_todo_priority
+                set_todo_recurrence=set_todo_recurrence.clone()
                 decrement_todo=decrement_todo
                 editing_todo_id=editing_todo_id
                 set_editing_todo_id=set_editing_todo_id
+                highlighted_id=highlighted_id
                 update_todo_text=update_todo_text
             />
         </li>
     }.into_any()
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TrashEntry {
+    id: u32,
+    text: String,
+    parent_id: Option<u32>,
+    list_name: String,
+    deleted_at: i64,
+    item_count: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TrashItemArgs {
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SetRetentionArgs {
+    days: u32,
+}
+
+fn format_age(timestamp_ms: i64) -> String {
+    let minutes = ((js_sys::Date::now() as i64 - timestamp_ms) / 60_000).max(0);
+    match minutes {
+        0 => "just now".to_string(),
+        m if m < 60 => format!("{}m ago", m),
+        m if m < 60 * 24 => format!("{}h ago", m / 60),
+        m => format!("{}d ago", m / (60 * 24)),
+    }
+}
+
+#[component]
+fn TrashView<F1, F2>(on_restored: F1, show_error: F2) -> impl IntoView
+where
+    F1: Fn() + Copy + Send + 'static,
+    F2: Fn(AppError) + Copy + Send + 'static,
+{
+    let (entries, set_entries) = signal(Vec::<TrashEntry>::new());
+    let (retention_days, set_retention_days) = signal(30u32);
+
+    let reload = move || async move {
+        match invoke_cmd::<Vec<TrashEntry>>("load_trash", JsValue::NULL).await {
+            Ok(list) => set_entries.set(list),
+            Err(e) => show_error(e),
+        }
+    };
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            if let Ok(days) = invoke_cmd::<u32>("get_trash_retention_days", JsValue::NULL).await {
+                set_retention_days.set(days);
+            }
+            reload().await;
+        });
+    });
+
+    let run = move |cmd: &'static str, id: u32| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&TrashItemArgs { id }).unwrap();
+            match invoke_cmd::<()>(cmd, args).await {
+                Ok(()) => on_restored(),
+                Err(e) => show_error(e),
+            }
+            reload().await;
+        });
+    };
+
+    let empty_trash = move |_| {
+        spawn_local(async move {
+            if let Err(e) = invoke_cmd::<usize>("empty_trash", JsValue::NULL).await {
+                show_error(e);
+            }
+            reload().await;
+        });
+    };
+
+    let set_retention = move |ev| {
+        let Ok(days) = event_target_value(&ev).parse::<u32>() else {
+            return;
+        };
+        set_retention_days.set(days);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetRetentionArgs { days }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("set_trash_retention_days", args).await {
+                show_error(e);
+            }
+            reload().await;
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 text-sm">
+            <div class="flex items-center gap-2 text-xs text-gray-600">
+                <span>"Purge after"</span>
+                <input
+                    type="number"
+                    min="1"
+                    class="w-12 p-1 border rounded text-center"
+                    prop:value=move || retention_days.get().to_string()
+                    on:change=set_retention
+                />
+                <span class="flex-1">"days"</span>
+                <button
+                    class="text-red-500 hover:text-red-700 disabled:opacity-40"
+                    disabled=move || entries.get().is_empty()
+                    on:click=empty_trash
+                >
+                    "Empty trash"
+                </button>
+            </div>
+            {move || entries.get().is_empty().then(|| view! {
+                <div class="text-xs text-gray-500 italic">"Trash is empty"</div>
+            })}
+            <ul class="flex flex-col gap-1">
+                <For
+                    each=move || entries.get()
+                    key=|entry| (entry.id, entry.deleted_at)
+                    children=move |entry| {
+                        let id = entry.id;
+                        view! {
+                            <li class="flex items-center gap-2 p-2 rounded bg-white/70 border">
+                                <span class="flex-1 truncate text-gray-700" inner_html=render_todo_markdown(&entry.text)></span>
+                                {(entry.item_count > 1).then(|| view! {
+                                    <span class="text-xs text-gray-400">{format!("+{}", entry.item_count - 1)}</span>
+                                })}
+                                <span class="text-xs text-gray-400 truncate max-w-[5rem]" title="List">{entry.list_name.clone()}</span>
+                                <span class="text-xs text-gray-400">{format_age(entry.deleted_at)}</span>
+                                <button
+                                    class="text-xs text-green-600 hover:text-green-800"
+                                    title="Restore to its original place"
+                                    on:click=move |_| run("restore_todo_item", id)
+                                >
+                                    "Restore"
+                                </button>
+                                <button
+                                    class="text-xs text-red-400 hover:text-red-600"
+                                    title="Delete forever"
+                                    on:click=move |_| run("purge_todo_item", id)
+                                >
+                                    "×"
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}
+
+/// Mirrors the backend `backup::RestoreMode`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RestoreMode {
+    Replace,
+    Merge,
+}
+
+/// Mirrors the backend `backup::RestoreSummary`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct RestoreSummary {
+    lists: u32,
+    todos: u32,
+    notes: u32,
+    views: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RestoreBackupArgs {
+    json: String,
+    mode: RestoreMode,
+}
+
+/// Saves a JSON backup of everything to a chosen file, or loads one back in.
+/// Replacing asks first and restarts the app once the summary is shown;
+/// merging adds the backup's lists and notes next to the current ones.
+#[component]
+fn BackupView<F1, F2>(on_restored: F1, show_error: F2) -> impl IntoView
+where
+    F1: Fn() + Copy + Send + 'static,
+    F2: Fn(AppError) + Copy + Send + 'static,
+{
+    let (mode, set_mode) = signal(RestoreMode::Merge);
+    let (notice, set_notice) = signal(None::<String>);
+    // A replace waiting for confirmation: file name and contents
+    let (pending, set_pending) = signal(None::<(String, String)>);
+
+    let export = move |_| {
+        spawn_local(async move {
+            match invoke_cmd::<Option<String>>("export_backup", JsValue::NULL).await {
+                Ok(Some(path)) => set_notice.set(Some(format!("Saved to {}", path))),
+                Ok(None) => {}
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let restore = move |json: String, mode: RestoreMode| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&RestoreBackupArgs { json, mode }).unwrap();
+            match invoke_cmd::<RestoreSummary>("restore_backup", args).await {
+                Ok(summary) if mode == RestoreMode::Replace => {
+                    set_notice.set(Some(format!(
+                        "Restored {} lists, {} items, {} notes and {} views. Restarting...",
+                        summary.lists, summary.todos, summary.notes, summary.views
+                    )));
+                    // Windows reopen the way the backup has them
+                    set_timeout(
+                        move || {
+                            spawn_local(async move {
+                                if let Err(e) = invoke_cmd::<()>("restart_app", JsValue::NULL).await {
+                                    show_error(e);
+                                }
+                            })
+                        },
+                        Duration::from_secs(2),
+                    );
+                }
+                Ok(summary) => {
+                    set_notice.set(Some(format!(
+                        "Added {} lists, {} items, {} notes and {} views",
+                        summary.lists, summary.todos, summary.notes, summary.views
+                    )));
+                    on_restored();
+                }
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let open_file = move |ev: web_sys::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        input.set_value("");
+        set_notice.set(None);
+        spawn_local(async move {
+            let json = match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                Ok(text) => text.as_string().unwrap_or_default(),
+                Err(_) => return set_notice.set(Some(format!("Could not read {}", file.name()))),
+            };
+            match mode.get_untracked() {
+                RestoreMode::Replace => set_pending.set(Some((file.name(), json))),
+                RestoreMode::Merge => restore(json, RestoreMode::Merge),
+            }
+        });
+    };
+
+    let confirm_replace = move |_| {
+        if let Some((_, json)) = pending.get_untracked() {
+            set_pending.set(None);
+            restore(json, RestoreMode::Replace);
+        }
+    };
+
+    view! {
+        <div class="flex flex-col gap-3 text-sm">
+            <div class="flex flex-col gap-1 p-2 rounded bg-white/70 border">
+                <div class="text-xs text-gray-600">
+                    "Lists, items, notes, saved views, schedules, windows and settings, as one JSON file."
+                </div>
+                <button class="self-start text-xs text-blue-600 hover:text-blue-800" on:click=export>
+                    "Export backup"
+                </button>
+            </div>
+            <div class="flex flex-col gap-1 p-2 rounded bg-white/70 border text-xs text-gray-600">
+                <label class="flex items-center gap-1">
+                    <input
+                        type="radio"
+                        name="restore-mode"
+                        prop:checked=move || mode.get() == RestoreMode::Merge
+                        on:change=move |_| set_mode.set(RestoreMode::Merge)
+                    />
+                    "Merge: add next to what is here"
+                </label>
+                <label class="flex items-center gap-1">
+                    <input
+                        type="radio"
+                        name="restore-mode"
+                        prop:checked=move || mode.get() == RestoreMode::Replace
+                        on:change=move |_| set_mode.set(RestoreMode::Replace)
+                    />
+                    "Replace: delete everything here first"
+                </label>
+                <label class="self-start text-blue-600 hover:text-blue-800 cursor-pointer">
+                    "Restore from file..."
+                    <input type="file" class="hidden" accept=".json,application/json" on:change=open_file />
+                </label>
+            </div>
+            {move || pending.get().map(|(name, _)| view! {
+                <div class="flex items-center gap-2 px-2 py-1 rounded bg-red-50 border border-red-300 text-xs text-red-700">
+                    <span class="flex-1">{format!("Replace everything with {}? This cannot be undone.", name)}</span>
+                    <button class="font-bold hover:text-red-900" on:click=confirm_replace>"Replace"</button>
+                    <button class="hover:text-red-900" on:click=move |_| set_pending.set(None)>"Cancel"</button>
+                </div>
+            })}
+            {move || notice.get().map(|notice| view! {
+                <div class="text-[10px] text-gray-500 break-all">{notice}</div>
+            })}
+        </div>
+    }
+}
+
+/// Mirrors the backend `revisions::NoteRevision`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct NoteRevision {
+    id: u32,
+    note_id: u32,
+    created_at: i64,
+    preview: String,
+    length: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DiffOp {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct DiffLine {
+    op: DiffOp,
+    text: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NoteIdArgs {
+    note_id: u32,
+}
+
+/// Earlier texts of a note, each shown as a diff against the current text
+/// and restorable.
+#[component]
+fn RevisionsView<F1, F2>(note_id: u32, on_restored: F1, show_error: F2) -> impl IntoView
+where
+    F1: Fn() + Copy + Send + 'static,
+    F2: Fn(AppError) + Copy + Send + 'static,
+{
+    let (revisions, set_revisions) = signal(Vec::<NoteRevision>::new());
+    let (selected, set_selected) = signal(None::<u32>);
+    let (diff, set_diff) = signal(Vec::<DiffLine>::new());
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&NoteIdArgs { note_id }).unwrap();
+            match invoke_cmd::<Vec<NoteRevision>>("load_note_revisions", args).await {
+                Ok(list) => set_revisions.set(list),
+                Err(e) => show_error(e),
+            }
+        });
+    });
+
+    let select = move |id: u32| {
+        set_selected.set(Some(id));
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            match invoke_cmd::<Vec<DiffLine>>("diff_note_revision", args).await {
+                Ok(lines) if selected.get_untracked() == Some(id) => set_diff.set(lines),
+                Ok(_) => {}
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let restore = move |_| {
+        let Some(id) = selected.get_untracked() else {
+            return;
+        };
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            match invoke_cmd::<u32>("restore_note_revision", args).await {
+                Ok(_) => on_restored(),
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 h-full text-sm">
+            {move || revisions.get().is_empty().then(|| view! {
+                <div class="text-xs text-gray-500 italic">"No earlier versions of this note yet"</div>
+            })}
+            <ul class="flex flex-col gap-1 max-h-32 overflow-auto">
+                <For
+                    each=move || revisions.get()
+                    key=|revision| revision.id
+                    children=move |revision| {
+                        let id = revision.id;
+                        view! {
+                            <li
+                                class=move || format!(
+                                    "flex items-center gap-2 px-2 py-1 rounded border cursor-pointer {}",
+                                    if selected.get() == Some(id) { "bg-yellow-200 border-yellow-400" } else { "bg-white/70 hover:bg-yellow-50" }
+                                )
+                                on:click=move |_| select(id)
+                            >
+                                <span class="text-xs text-gray-500 w-14 shrink-0">{format_age(revision.created_at)}</span>
+                                <span class="flex-1 truncate text-gray-700">{revision.preview.clone()}</span>
+                                <span class="text-xs text-gray-400">{format!("{} chars", revision.length)}</span>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+            {move || selected.get().is_some().then(|| view! {
+                <div class="flex items-center gap-2 text-xs text-gray-500">
+                    <span class="flex-1">
+                        <span class="text-red-600">"− this version"</span>
+                        " / "
+                        <span class="text-green-700">"+ current text"</span>
+                    </span>
+                    <button class="text-green-600 hover:text-green-800" on:click=restore>
+                        "Restore this version"
+                    </button>
+                </div>
+                <pre class="flex-1 overflow-auto p-1 rounded bg-white/70 border text-xs font-mono whitespace-pre-wrap">
+                    {move || diff.get().into_iter().map(|line| {
+                        let (class, sign) = match line.op {
+                            DiffOp::Same => ("text-gray-600", "  "),
+                            DiffOp::Added => ("bg-green-100 text-green-800", "+ "),
+                            DiffOp::Removed => ("bg-red-100 text-red-800", "− "),
+                        };
+                        view! { <div class=class>{sign}{line.text}</div> }
+                    }).collect_view()}
+                </pre>
+            })}
+        </div>
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ArchivedItem {
+    id: u32,
+    text: String,
+    depth: u32,
+    completed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ArchiveEntry {
+    id: u32,
+    text: String,
+    list_name: String,
+    archived_at: i64,
+    items: Vec<ArchivedItem>,
+}
+
+#[component]
+fn ArchiveView<F1, F2>(on_restored: F1, show_error: F2) -> impl IntoView
+where
+    F1: Fn() + Copy + Send + 'static,
+    F2: Fn(AppError) + Copy + Send + 'static,
+{
+    let (entries, set_entries) = signal(Vec::<ArchiveEntry>::new());
+    let (auto_days, set_auto_days) = signal(0u32);
+    let (filter, set_filter) = signal(String::new());
+    let (expanded, set_expanded) = signal(Vec::<u32>::new());
+
+    let reload = move || async move {
+        match invoke_cmd::<Vec<ArchiveEntry>>("load_archive", JsValue::NULL).await {
+            Ok(list) => set_entries.set(list),
+            Err(e) => show_error(e),
+        }
+    };
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            if let Ok(days) = invoke_cmd::<u32>("get_auto_archive_days", JsValue::NULL).await {
+                set_auto_days.set(days);
+            }
+            reload().await;
+        });
+    });
+
+    let run = move |cmd: &'static str, id: u32| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            match invoke_cmd::<()>(cmd, args).await {
+                Ok(()) => on_restored(),
+                Err(e) => show_error(e),
+            }
+            reload().await;
+        });
+    };
+
+    let set_auto_archive = move |ev| {
+        let days = event_target_value(&ev).parse::<u32>().unwrap_or(0);
+        set_auto_days.set(days);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SetRetentionArgs { days }).unwrap();
+            match invoke_cmd::<()>("set_auto_archive_days", args).await {
+                Ok(()) => on_restored(),
+                Err(e) => show_error(e),
+            }
+            reload().await;
+        });
+    };
+
+    // Entries whose own text or any archived sub-item contains the filter
+    let visible = move || {
+        let needle = filter.get().to_lowercase();
+        entries
+            .get()
+            .into_iter()
+            .filter(|e| {
+                needle.is_empty()
+                    || e.text.to_lowercase().contains(&needle)
+                    || e.items.iter().any(|i| i.text.to_lowercase().contains(&needle))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 text-sm">
+            <div class="flex items-center gap-2 text-xs text-gray-600">
+                <span>"Archive done items after"</span>
+                <input
+                    type="number"
+                    min="0"
+                    class="w-12 p-1 border rounded text-center"
+                    title="0 turns auto-archiving off"
+                    prop:value=move || auto_days.get().to_string()
+                    on:change=set_auto_archive
+                />
+                <span>"days"</span>
+                <input
+                    type="search"
+                    class="flex-1 min-w-0 p-1 border rounded"
+                    placeholder="Filter…"
+                    prop:value=move || filter.get()
+                    on:input=move |ev| set_filter.set(event_target_value(&ev))
+                />
+            </div>
+            {move || entries.get().is_empty().then(|| view! {
+                <div class="text-xs text-gray-500 italic">"Archive is empty"</div>
+            })}
+            <ul class="flex flex-col gap-1">
+                <For
+                    each=visible
+                    key=|entry| (entry.id, entry.archived_at)
+                    children=move |entry| {
+                        let id = entry.id;
+                        let has_items = !entry.items.is_empty();
+                        let items = entry.items.clone();
+                        let is_expanded = move || expanded.get().contains(&id);
+                        view! {
+                            <li class="flex flex-col gap-1 p-2 rounded bg-white/70 border">
+                                <div class="flex items-center gap-2">
+                                    <button
+                                        class="w-3 text-xs text-gray-400 hover:text-gray-700 disabled:invisible"
+                                        disabled=!has_items
+                                        on:click=move |_| set_expanded.update(|e| {
+                                            if e.contains(&id) {
+                                                e.retain(|x| *x != id);
+                                            } else {
+                                                e.push(id);
+                                            }
+                                        })
+                                    >
+                                        {move || if is_expanded() { "▾" } else { "▸" }}
+                                    </button>
+                                    <span class="flex-1 truncate text-gray-700" inner_html=render_todo_markdown(&entry.text)></span>
+                                    {has_items.then(|| view! {
+                                        <span class="text-xs text-gray-400">{format!("+{}", entry.items.len())}</span>
+                                    })}
+                                    <span class="text-xs text-gray-400 truncate max-w-[5rem]" title="List">{entry.list_name.clone()}</span>
+                                    <span class="text-xs text-gray-400">{format_age(entry.archived_at)}</span>
+                                    <button
+                                        class="text-xs text-green-600 hover:text-green-800"
+                                        title="Restore to its original place"
+                                        on:click=move |_| run("restore_archived_item", id)
+                                    >
+                                        "Restore"
+                                    </button>
+                                    <button
+                                        class="text-xs text-red-400 hover:text-red-600"
+                                        title="Delete forever"
+                                        on:click=move |_| run("purge_archived_item", id)
+                                    >
+                                        "×"
+                                    </button>
+                                </div>
+                                {move || is_expanded().then(|| view! {
+                                    <ul class="flex flex-col text-xs text-gray-600">
+                                        {items.iter().map(|item| view! {
+                                            <li
+                                                class=if item.completed { "line-through text-gray-400" } else { "" }
+                                                style=format!("padding-left: {}rem", item.depth as f32 * 0.75 + 0.75)
+                                                inner_html=render_todo_markdown(&item.text)
+                                            ></li>
+                                        }).collect_view()}
+                                    </ul>
+                                })}
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ListItemArgs {
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ListNameArgs {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RenameListArgs {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MoveListArgs {
+    id: u32,
+    position: i32,
+}
+
+/// Title bar dropdown to switch between, add, rename, reorder and delete lists.
+#[component]
+fn ListSwitcher<F1, F2, F3>(
+    lists: ReadSignal<Vec<List>>,
+    active_list: ReadSignal<u32>,
+    on_select: F1,
+    on_changed: F2,
+    show_error: F3,
+) -> impl IntoView
+where
+    F1: Fn(u32) + Copy + Send + 'static,
+    F2: Fn() + Copy + Send + 'static,
+    F3: Fn(AppError) + Copy + Send + 'static,
+{
+    let (open, set_open) = signal(false);
+    let (renaming_id, set_renaming_id) = signal(None::<u32>);
+    // Outcome of the last export, shown under the buttons
+    let (export_notice, set_export_notice) = signal(None::<String>);
+
+    let active_name = move || {
+        lists
+            .get()
+            .into_iter()
+            .find(|l| l.id == active_list.get())
+            .map(|l| l.name)
+            .unwrap_or_else(|| "TodoList".to_string())
+    };
+
+    let run = move |cmd: &'static str, args: JsValue| {
+        spawn_local(async move {
+            if let Err(e) = invoke_cmd::<()>(cmd, args).await {
+                show_error(e);
+            }
+            on_changed();
+        });
+    };
+
+    let create = move |ev: web_sys::KeyboardEvent| {
+        if ev.key() != "Enter" {
+            return;
+        }
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let name = input.value();
+        if name.trim().is_empty() {
+            return;
+        }
+        input.set_value("");
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListNameArgs { name }).unwrap();
+            match invoke_cmd::<u32>("create_list", args).await {
+                Ok(id) => {
+                    on_changed();
+                    on_select(id);
+                    set_open.set(false);
+                }
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let rename = move |id: u32, name: String| {
+        // Escape already cleared it, the blur that follows is not a save
+        if renaming_id.get_untracked() != Some(id) {
+            return;
+        }
+        set_renaming_id.set(None);
+        let args = serde_wasm_bindgen::to_value(&RenameListArgs { id, name }).unwrap();
+        run("rename_list", args);
+    };
+
+    let copy_markdown = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListIdArgs { list_id: active_list.get_untracked() }).unwrap();
+            let markdown = match invoke_cmd::<String>("export_markdown", args).await {
+                Ok(markdown) => markdown,
+                Err(e) => return show_error(e),
+            };
+            let clipboard = web_sys::window().unwrap().navigator().clipboard();
+            match wasm_bindgen_futures::JsFuture::from(clipboard.write_text(&markdown)).await {
+                Ok(_) => set_export_notice.set(Some("Copied as Markdown".to_string())),
+                Err(_) => set_export_notice.set(Some("Could not copy to the clipboard".to_string())),
+            }
+        });
+    };
+
+    let save_markdown = move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListIdArgs { list_id: active_list.get_untracked() }).unwrap();
+            match invoke_cmd::<Option<String>>("export_markdown_file", args).await {
+                Ok(Some(path)) => set_export_notice.set(Some(format!("Saved to {}", path))),
+                Ok(None) => {}
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let import_file = move |ev: web_sys::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        // Let the same file be picked again
+        input.set_value("");
+        spawn_local(async move {
+            let markdown = match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                Ok(text) => text.as_string().unwrap_or_default(),
+                Err(_) => return set_export_notice.set(Some(format!("Could not read {}", file.name()))),
+            };
+            let args = serde_wasm_bindgen::to_value(&ImportMarkdownArgs {
+                list_id: active_list.get_untracked(),
+                parent_id: None,
+                markdown,
+            })
+            .unwrap();
+            match invoke_cmd::<Vec<u32>>("import_markdown", args).await {
+                Ok(_) => {
+                    set_export_notice.set(Some(format!("Imported {}", file.name())));
+                    on_changed();
+                }
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    view! {
+        <div class="relative" on:mousedown=move |ev| ev.stop_propagation()>
+            <button
+                class="text-xs text-yellow-800 font-bold hover:bg-yellow-300 rounded px-1 max-w-[10rem] truncate"
+                title="Switch list"
+                on:click=move |_| {
+                    set_export_notice.set(None);
+                    set_open.update(|o| *o = !*o);
+                }
+            >
+                {active_name}
+                " ▾"
+            </button>
+            {move || open.get().then(|| view! {
+                <div class="absolute top-full left-0 mt-1 w-52 bg-white rounded shadow-xl border border-gray-200 p-1 text-xs z-[9999]">
+                    <ul class="flex flex-col">
+                        <For
+                            each=move || lists.get()
+                            key=|list| (list.id, list.name.clone(), list.position)
+                            children=move |list| {
+                                let id = list.id;
+                                let name = list.name.clone();
+                                let last = move || lists.get().len() as i32 - 1;
+                                view! {
+                                    <li class=move || format!(
+                                        "flex items-center gap-1 px-1 py-0.5 rounded hover:bg-yellow-50 {}",
+                                        if active_list.get() == id { "bg-yellow-100 font-bold" } else { "" }
+                                    )>
+                                        {move || if renaming_id.get() == Some(id) {
+                                            view! {
+                                                <input
+                                                    class="flex-1 border rounded px-1 outline-none"
+                                                    prop:value=name.clone()
+                                                    on:blur=move |ev| rename(id, event_target_value(&ev))
+                                                    on:keydown=move |ev| {
+                                                        if ev.key() == "Enter" {
+                                                            rename(id, event_target_value(&ev));
+                                                        } else if ev.key() == "Escape" {
+                                                            set_renaming_id.set(None);
+                                                        }
+                                                    }
+                                                    autofocus
+                                                />
+                                            }.into_any()
+                                        } else {
+                                            view! {
+                                                <span
+                                                    class="flex-1 truncate cursor-pointer"
+                                                    title="Double-click to rename"
+                                                    on:click=move |_| {
+                                                        on_select(id);
+                                                        set_open.set(false);
+                                                    }
+                                                    on:dblclick=move |_| set_renaming_id.set(Some(id))
+                                                >
+                                                    {name.clone()}
+                                                </span>
+                                            }.into_any()
+                                        }}
+                                        <button
+                                            class="text-gray-400 hover:text-gray-700 disabled:opacity-30"
+                                            title="Move up"
+                                            disabled=list.position == 0
+                                            on:click=move |_| run("move_list", serde_wasm_bindgen::to_value(&MoveListArgs { id, position: list.position - 1 }).unwrap())
+                                        >
+                                            "↑"
+                                        </button>
+                                        <button
+                                            class="text-gray-400 hover:text-gray-700 disabled:opacity-30"
+                                            title="Move down"
+                                            disabled=move || list.position >= last()
+                                            on:click=move |_| run("move_list", serde_wasm_bindgen::to_value(&MoveListArgs { id, position: list.position + 1 }).unwrap())
+                                        >
+                                            "↓"
+                                        </button>
+                                        <button
+                                            class="text-red-400 hover:text-red-600 disabled:opacity-30"
+                                            title="Delete list and all its items (Ctrl+Z to undo)"
+                                            disabled=move || last() == 0
+                                            on:click=move |_| run("delete_list", serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap())
+                                        >
+                                            "×"
+                                        </button>
+                                    </li>
+                                }
+                            }
+                        />
+                    </ul>
+                    <input
+                        class="w-full mt-1 border rounded px-1 py-0.5 outline-none"
+                        placeholder="New list..."
+                        on:keydown=create
+                    />
+                    <div class="flex gap-2 mt-1 pt-1 border-t text-gray-500">
+                        <button class="hover:text-gray-800" title="Copy this list as a Markdown checklist" on:click=copy_markdown>
+                            "Copy as Markdown"
+                        </button>
+                        <button class="hover:text-gray-800" title="Save this list as a Markdown file" on:click=save_markdown>
+                            "Save .md"
+                        </button>
+                        <label class="hover:text-gray-800 cursor-pointer" title="Add the items of a Markdown checklist file to this list">
+                            "Import .md"
+                            <input type="file" class="hidden" accept=".md,.markdown,.txt,text/markdown,text/plain" on:change=import_file />
+                        </label>
+                    </div>
+                    {move || export_notice.get().map(|notice| view! {
+                        <div class="mt-1 text-[10px] text-gray-500 break-all">{notice}</div>
+                    })}
+                </div>
+            })}
+        </div>
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct NoteTitleArgs {
+    title: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RenameNoteArgs {
+    id: u32,
+    title: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MoveNoteArgs {
+    id: u32,
+    position: i32,
+}
+
+/// Title bar dropdown to find, switch between, add, rename, reorder and
+/// delete notes, and to give one a window of its own.
+#[component]
+fn NoteSwitcher<F1, F2, F3>(
+    notes: ReadSignal<Vec<NoteSummary>>,
+    active_note: ReadSignal<u32>,
+    on_select: F1,
+    on_changed: F2,
+    show_error: F3,
+) -> impl IntoView
+where
+    F1: Fn(u32) + Copy + Send + 'static,
+    F2: Fn() + Copy + Send + 'static,
+    F3: Fn(AppError) + Copy + Send + 'static,
+{
+    let (open, set_open) = signal(false);
+    let (renaming_id, set_renaming_id) = signal(None::<u32>);
+    let (filter, set_filter) = signal(String::new());
+    // Deleting a note cannot be undone, so it takes a second click
+    let (deleting_id, set_deleting_id) = signal(None::<u32>);
+
+    let active_title = move || {
+        notes
+            .get()
+            .into_iter()
+            .find(|n| n.id == active_note.get())
+            .map(|n| n.title)
+            .unwrap_or_else(|| "Notes".to_string())
+    };
+
+    let shown = move || {
+        let filter = filter.get().to_lowercase();
+        notes
+            .get()
+            .into_iter()
+            .filter(|n| n.title.to_lowercase().contains(&filter))
+            .collect::<Vec<_>>()
+    };
+
+    let run = move |cmd: &'static str, args: JsValue| {
+        spawn_local(async move {
+            if let Err(e) = invoke_cmd::<()>(cmd, args).await {
+                show_error(e);
+            }
+            on_changed();
+        });
+    };
+
+    let create = move |ev: web_sys::KeyboardEvent| {
+        if ev.key() != "Enter" {
+            return;
+        }
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let title = input.value();
+        input.set_value("");
+        set_filter.set(String::new());
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&NoteTitleArgs { title }).unwrap();
+            match invoke_cmd::<u32>("create_note", args).await {
+                Ok(id) => {
+                    on_changed();
+                    on_select(id);
+                    set_open.set(false);
+                }
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let rename = move |id: u32, title: String| {
+        // Escape already cleared it, the blur that follows is not a save
+        if renaming_id.get_untracked() != Some(id) {
+            return;
+        }
+        set_renaming_id.set(None);
+        let args = serde_wasm_bindgen::to_value(&RenameNoteArgs { id, title }).unwrap();
+        run("rename_note", args);
+    };
+
+    let delete = move |id: u32| {
+        if deleting_id.get_untracked() != Some(id) {
+            set_deleting_id.set(Some(id));
+            return;
+        }
+        set_deleting_id.set(None);
+        run("delete_note", serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap());
+    };
+
+    view! {
+        <div class="relative" on:mousedown=move |ev| ev.stop_propagation()>
+            <button
+                class="text-xs text-yellow-800 font-bold hover:bg-yellow-300 rounded px-1 max-w-[10rem] truncate"
+                title="Switch note"
+                on:click=move |_| {
+                    set_deleting_id.set(None);
+                    set_open.update(|o| *o = !*o);
+                }
+            >
+                {active_title}
+                " ▾"
+            </button>
+            {move || open.get().then(|| view! {
+                <div class="absolute top-full left-0 mt-1 w-56 bg-white rounded shadow-xl border border-gray-200 p-1 text-xs z-[9999]">
+                    <input
+                        class="w-full mb-1 border rounded px-1 py-0.5 outline-none"
+                        placeholder="Find a note..."
+                        prop:value=filter
+                        on:input=move |ev| set_filter.set(event_target_value(&ev))
+                    />
+                    <ul class="flex flex-col max-h-60 overflow-auto">
+                        <For
+                            each=shown
+                            key=|note| (note.id, note.title.clone(), note.position, note.windowed)
+                            children=move |note| {
+                                let id = note.id;
+                                let title = note.title.clone();
+                                let last = move || notes.get().len() as i32 - 1;
+                                view! {
+                                    <li class=move || format!(
+                                        "flex items-center gap-1 px-1 py-0.5 rounded hover:bg-yellow-50 {}",
+                                        if active_note.get() == id { "bg-yellow-100 font-bold" } else { "" }
+                                    )>
+                                        {move || if renaming_id.get() == Some(id) {
+                                            view! {
+                                                <input
+                                                    class="flex-1 border rounded px-1 outline-none"
+                                                    prop:value=title.clone()
+                                                    on:blur=move |ev| rename(id, event_target_value(&ev))
+                                                    on:keydown=move |ev| {
+                                                        if ev.key() == "Enter" {
+                                                            rename(id, event_target_value(&ev));
+                                                        } else if ev.key() == "Escape" {
+                                                            set_renaming_id.set(None);
+                                                        }
+                                                    }
+                                                    autofocus
+                                                />
+                                            }.into_any()
+                                        } else {
+                                            view! {
+                                                <span
+                                                    class="flex-1 truncate cursor-pointer"
+                                                    title="Double-click to rename"
+                                                    on:click=move |_| {
+                                                        on_select(id);
+                                                        set_open.set(false);
+                                                    }
+                                                    on:dblclick=move |_| set_renaming_id.set(Some(id))
+                                                >
+                                                    {title.clone()}
+                                                </span>
+                                            }.into_any()
+                                        }}
+                                        <button
+                                            class=if note.windowed { "text-yellow-600 hover:text-yellow-800" } else { "text-gray-400 hover:text-gray-700" }
+                                            title="Open in its own sticky window"
+                                            on:click=move |_| run("open_note_window", serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap())
+                                        >
+                                            "⧉"
+                                        </button>
+                                        <button
+                                            class="text-gray-400 hover:text-gray-700 disabled:opacity-30"
+                                            title="Move up"
+                                            disabled=note.position == 0
+                                            on:click=move |_| run("move_note", serde_wasm_bindgen::to_value(&MoveNoteArgs { id, position: note.position - 1 }).unwrap())
+                                        >
+                                            "↑"
+                                        </button>
+                                        <button
+                                            class="text-gray-400 hover:text-gray-700 disabled:opacity-30"
+                                            title="Move down"
+                                            disabled=move || note.position >= last()
+                                            on:click=move |_| run("move_note", serde_wasm_bindgen::to_value(&MoveNoteArgs { id, position: note.position + 1 }).unwrap())
+                                        >
+                                            "↓"
+                                        </button>
+                                        <button
+                                            class="text-red-400 hover:text-red-600 disabled:opacity-30"
+                                            title=move || if deleting_id.get() == Some(id) { "Click again to delete for good" } else { "Delete note" }
+                                            disabled=move || last() == 0
+                                            on:click=move |_| delete(id)
+                                        >
+                                            {move || if deleting_id.get() == Some(id) { "Delete?" } else { "×" }}
+                                        </button>
+                                    </li>
+                                }
+                            }
+                        />
+                    </ul>
+                    <input
+                        class="w-full mt-1 border rounded px-1 py-0.5 outline-none"
+                        placeholder="New note..."
+                        on:keydown=create
+                    />
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Mirrors the backend `schedule::ResetFrequency`. Weekday 0 = Monday.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ResetFrequency {
+    Daily,
+    Weekly { weekday: u8 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ResetSchedule {
+    id: u32,
+    list_id: u32,
+    todo_id: Option<u32>,
+    todo_text: Option<String>,
+    frequency: ResetFrequency,
+    time: String,
+    last_reset_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddResetScheduleArgs {
+    list_id: u32,
+    todo_id: Option<u32>,
+    frequency: ResetFrequency,
+    time: String,
+}
+
+/// Lists and edits the automatic reset schedules of one list.
+#[component]
+fn ResetSchedulesView<F>(list_id: u32, todos: ReadSignal<Vec<TodoItem>>, show_error: F) -> impl IntoView
+where
+    F: Fn(AppError) + Copy + Send + 'static,
+{
+    let (schedules, set_schedules) = signal(Vec::<ResetSchedule>::new());
+    let (scope, set_scope) = signal(None::<u32>);
+    // None = daily, Some(weekday) = weekly
+    let (weekday, set_weekday) = signal(None::<u8>);
+    let (time, set_time) = signal("04:00".to_string());
+
+    let reload = move || async move {
+        let args = serde_wasm_bindgen::to_value(&ListIdArgs { list_id }).unwrap();
+        match invoke_cmd::<Vec<ResetSchedule>>("load_reset_schedules", args).await {
+            Ok(list) => set_schedules.set(list),
+            Err(e) => show_error(e),
+        }
+    };
+
+    Effect::new(move |_| spawn_local(reload()));
+
+    let add = move |_| {
+        let frequency = match weekday.get_untracked() {
+            Some(weekday) => ResetFrequency::Weekly { weekday },
+            None => ResetFrequency::Daily,
+        };
+        let args = serde_wasm_bindgen::to_value(&AddResetScheduleArgs {
+            list_id,
+            todo_id: scope.get_untracked(),
+            frequency,
+            time: time.get_untracked(),
+        })
+        .unwrap();
+        spawn_local(async move {
+            if let Err(e) = invoke_cmd::<u32>("add_reset_schedule", args).await {
+                show_error(e);
+            }
+            reload().await;
+        });
+    };
+
+    let delete = move |id: u32| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("delete_reset_schedule", args).await {
+                show_error(e);
+            }
+            reload().await;
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 text-sm">
+            <div class="flex flex-wrap items-center gap-1 text-xs text-gray-600">
+                <span>"Reset"</span>
+                <select
+                    class="border rounded px-1 bg-white max-w-[8rem]"
+                    on:change=move |ev| set_scope.set(event_target_value(&ev).parse::<u32>().ok())
+                >
+                    <option value="">"whole list"</option>
+                    {move || todos.get().into_iter().map(|t| view! {
+                        <option value=t.id.to_string()>{t.text}</option>
+                    }).collect_view()}
+                </select>
+                <select
+                    class="border rounded px-1 bg-white"
+                    on:change=move |ev| set_weekday.set(event_target_value(&ev).parse::<u8>().ok())
+                >
+                    <option value="">"every day"</option>
+                    {WEEKDAY_LABELS.iter().enumerate().map(|(day, label)| view! {
+                        <option value=day.to_string()>{format!("every {}", label)}</option>
+                    }).collect_view()}
+                </select>
+                <span>"at"</span>
+                <input
+                    type="time"
+                    class="border rounded px-1 bg-white"
+                    prop:value=move || time.get()
+                    on:change=move |ev| set_time.set(event_target_value(&ev))
+                />
+                <button class="text-green-600 hover:text-green-800 font-bold" on:click=add>"+"</button>
+            </div>
+            {move || schedules.get().is_empty().then(|| view! {
+                <div class="text-xs text-gray-500 italic">"No automatic resets for this list"</div>
+            })}
+            <ul class="flex flex-col gap-1">
+                <For
+                    each=move || schedules.get()
+                    key=|schedule| (schedule.id, schedule.last_reset_at)
+                    children=move |schedule| {
+                        let id = schedule.id;
+                        let when = match schedule.frequency {
+                            ResetFrequency::Daily => format!("Every day at {}", schedule.time),
+                            ResetFrequency::Weekly { weekday } => format!(
+                                "Every {} at {}",
+                                WEEKDAY_LABELS.get(weekday as usize).copied().unwrap_or("?"),
+                                schedule.time
+                            ),
+                        };
+                        let what = schedule.todo_text.clone().unwrap_or_else(|| "Whole list".to_string());
+                        view! {
+                            <li class="flex items-center gap-2 p-2 rounded bg-white/70 border">
+                                <span class="flex-1 truncate text-gray-700">{what}</span>
+                                <span class="text-xs text-gray-500">{when}</span>
+                                <span class="text-xs text-gray-400" title="Last reset">{format_age(schedule.last_reset_at)}</span>
+                                <button
+                                    class="text-xs text-red-400 hover:text-red-600"
+                                    title="Remove schedule"
+                                    on:click=move |_| delete(id)
+                                >
+                                    "×"
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct DayCount {
+    date: String,
+    count: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    fn unit(self, n: u32) -> String {
+        let unit = match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        };
+        format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Streak {
+    todo_id: u32,
+    text: String,
+    period: Period,
+    current: u32,
+    longest: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ListRate {
+    list_id: u32,
+    name: String,
+    completed: u32,
+    total: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Stats {
+    days: Vec<DayCount>,
+    weeks: Vec<DayCount>,
+    streaks: Vec<Streak>,
+    lists: Vec<ListRate>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatsArgs {
+    days: u32,
+}
+
+/// Days covered by the heatmap, about half a year.
+const STATS_DAYS: u32 = 182;
+
+fn heat_class(count: u32, max: u32) -> &'static str {
+    match count {
+        0 => "bg-yellow-100",
+        c if c * 4 <= max => "bg-green-200",
+        c if c * 2 <= max => "bg-green-400",
+        c if c * 4 <= max * 3 => "bg-green-600",
+        _ => "bg-green-800",
+    }
+}
+
+/// Bars scaled to the largest count, labelled with `label` of each entry.
+fn bar_chart(entries: Vec<DayCount>, label: fn(&str) -> String) -> impl IntoView {
+    let max = entries.iter().map(|e| e.count).max().unwrap_or(0).max(1);
+    view! {
+        <div class="flex items-end gap-0.5 h-20">
+            {entries.into_iter().map(|entry| {
+                let height = entry.count * 100 / max;
+                view! {
+                    <div class="flex-1 flex flex-col items-center justify-end h-full" title=format!("{}: {}", entry.date, entry.count)>
+                        <div class="w-full bg-green-500 rounded-t" style=format!("height: {}%", height)></div>
+                        <span class="text-[9px] text-gray-500">{label(&entry.date)}</span>
+                    </div>
+                }
+            }).collect_view()}
+        </div>
+    }
+}
+
+#[component]
+fn StatsView<F>(show_error: F) -> impl IntoView
+where
+    F: Fn(AppError) + Copy + Send + 'static,
+{
+    let (stats, set_stats) = signal(None::<Stats>);
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&StatsArgs { days: STATS_DAYS }).unwrap();
+            match invoke_cmd::<Stats>("load_stats", args).await {
+                Ok(loaded) => set_stats.set(Some(loaded)),
+                Err(e) => show_error(e),
+            }
+        });
+    });
+
+    view! {
+        <div class="flex flex-col gap-3 text-sm">
+            {move || stats.get().map(|stats| {
+                let today = stats.days.last().map(|d| d.count).unwrap_or(0);
+                let this_week = stats.weeks.last().map(|w| w.count).unwrap_or(0);
+                let recent_days = stats.days[stats.days.len().saturating_sub(14)..].to_vec();
+                let recent_weeks = stats.weeks[stats.weeks.len().saturating_sub(8)..].to_vec();
+                let max = stats.days.iter().map(|d| d.count).max().unwrap_or(0);
+                let weeks = stats.days.chunks(7).map(|w| w.to_vec()).collect::<Vec<_>>();
+
+                view! {
+                    <div class="flex gap-2 text-xs">
+                        <div class="flex-1 p-2 rounded bg-white/70 border">
+                            <div class="text-gray-500">"Done today"</div>
+                            <div class="text-lg font-bold text-gray-800">{today}</div>
+                        </div>
+                        <div class="flex-1 p-2 rounded bg-white/70 border">
+                            <div class="text-gray-500">"This week"</div>
+                            <div class="text-lg font-bold text-gray-800">{this_week}</div>
+                        </div>
+                    </div>
+
+                    <section>
+                        <h3 class="text-xs font-semibold text-gray-600 mb-1">"Last 14 days"</h3>
+                        {bar_chart(recent_days, |date| date[8..].to_string())}
+                    </section>
+
+                    <section>
+                        <h3 class="text-xs font-semibold text-gray-600 mb-1">"Per week"</h3>
+                        {bar_chart(recent_weeks, short_date)}
+                    </section>
+
+                    <section>
+                        <h3 class="text-xs font-semibold text-gray-600 mb-1">"Activity"</h3>
+                        <div class="flex gap-0.5 overflow-x-auto">
+                            {weeks.into_iter().map(|week| view! {
+                                <div class="flex flex-col gap-0.5">
+                                    {week.into_iter().map(|day| view! {
+                                        <div
+                                            class=format!("w-2.5 h-2.5 rounded-sm {}", heat_class(day.count, max))
+                                            title=format!("{}: {} done", day.date, day.count)
+                                        ></div>
+                                    }).collect_view()}
+                                </div>
+                            }).collect_view()}
+                        </div>
+                    </section>
+
+                    <section>
+                        <h3 class="text-xs font-semibold text-gray-600 mb-1">"Streaks"</h3>
+                        {stats.streaks.is_empty().then(|| view! {
+                            <div class="text-xs text-gray-500 italic">"Complete a repeating item or countdown to start a streak"</div>
+                        })}
+                        <ul class="flex flex-col gap-1">
+                            {stats.streaks.into_iter().map(|streak| view! {
+                                <li class="flex items-center gap-2 px-2 py-1 rounded bg-white/70 border" data-todo-id=streak.todo_id>
+                                    <span class="flex-1 truncate text-gray-700">{streak.text}</span>
+                                    <span class="text-xs text-orange-600" title="Current streak">
+                                        {format!("🔥 {}", streak.period.unit(streak.current))}
+                                    </span>
+                                    <span class="text-xs text-gray-400" title="Longest streak">
+                                        {format!("best {}", streak.longest)}
+                                    </span>
+                                </li>
+                            }).collect_view()}
+                        </ul>
+                    </section>
+
+                    <section>
+                        <h3 class="text-xs font-semibold text-gray-600 mb-1">"Completion by list"</h3>
+                        <ul class="flex flex-col gap-1">
+                            {stats.lists.into_iter().map(|list| {
+                                let percent = (list.completed * 100).checked_div(list.total).unwrap_or(0);
+                                view! {
+                                    <li class="flex items-center gap-2 text-xs" data-list-id=list.list_id>
+                                        <span class="w-24 truncate text-gray-700">{list.name}</span>
+                                        <div class="flex-1 h-2 rounded bg-yellow-100 overflow-hidden">
+                                            <div class="h-full bg-green-500" style=format!("width: {}%", percent)></div>
+                                        </div>
+                                        <span class="w-16 text-right text-gray-500">
+                                            {format!("{}/{} ({}%)", list.completed, list.total, percent)}
+                                        </span>
+                                    </li>
+                                }
+                            }).collect_view()}
+                        </ul>
+                    </section>
+                }
+            })}
+        </div>
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HitKind {
+    Todo,
+    Note,
+}
+
+/// A backend search result. Matches in `snippet` are wrapped in
+/// `\u{2}`..`\u{3}`.
+#[derive(Clone, Debug, Deserialize)]
+struct SearchHit {
+    kind: HitKind,
+    id: u32,
+    list_id: Option<u32>,
+    list_name: Option<String>,
+    snippet: String,
+    path: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SearchArgs {
+    query: String,
+}
+
+/// Renders a snippet with its matches in `<mark>`.
+fn highlighted_snippet(snippet: &str) -> impl IntoView {
+    let mut parts = snippet.split('\u{2}');
+    let lead = parts.next().unwrap_or_default().to_string();
+    let marked = parts
+        .map(|part| {
+            let (matched, rest) = part.split_once('\u{3}').unwrap_or((part, ""));
+            view! {
+                <mark class="bg-yellow-300 rounded-sm">{matched.to_string()}</mark>
+                {rest.to_string()}
+            }
+        })
+        .collect_view();
+    view! { {lead} {marked} }
+}
+
+#[component]
+fn SearchBox<F1, F2>(on_select: F1, show_error: F2) -> impl IntoView
+where
+    F1: Fn(SearchHit) + Copy + Send + 'static,
+    F2: Fn(AppError) + Copy + Send + 'static,
+{
+    let (query, set_query) = signal(String::new());
+    let (hits, set_hits) = signal(Vec::<SearchHit>::new());
+    let (open, set_open) = signal(false);
+    // Answers to older keystrokes may arrive after newer ones
+    let (generation, set_generation) = signal(0u32);
+
+    let run_search = move |text: String| {
+        set_query.set(text.clone());
+        let current = generation.get_untracked() + 1;
+        set_generation.set(current);
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&SearchArgs { query: text }).unwrap();
+            match invoke_cmd::<Vec<SearchHit>>("search", args).await {
+                Ok(found) if generation.get_untracked() == current => set_hits.set(found),
+                Ok(_) => {}
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let select = move |hit: SearchHit| {
+        set_open.set(false);
+        set_query.set(String::new());
+        set_hits.set(Vec::new());
+        on_select(hit);
+    };
+
+    view! {
+        <div class="relative flex-1 mx-2 min-w-0" on:mousedown=move |ev| ev.stop_propagation()>
+            <input
+                type="search"
+                class="w-full bg-yellow-50 border border-yellow-300 rounded px-1 py-0.5 text-xs outline-none focus:ring-1 focus:ring-yellow-500"
+                placeholder="Search…"
+                prop:value=move || query.get()
+                on:input=move |ev| run_search(event_target_value(&ev))
+                on:focus=move |_| set_open.set(true)
+                on:blur=move |_| set_open.set(false)
+                on:keydown=move |ev: web_sys::KeyboardEvent| match ev.key().as_str() {
+                    "Enter" => {
+                        if let Some(hit) = hits.get_untracked().into_iter().next() {
+                            select(hit);
+                        }
+                    }
+                    "Escape" => {
+                        set_query.set(String::new());
+                        set_hits.set(Vec::new());
+                    }
+                    _ => {}
+                }
+            />
+            {move || (open.get() && !query.get().trim().is_empty()).then(|| view! {
+                <ul class="absolute left-0 right-0 top-full mt-1 z-50 max-h-64 overflow-auto bg-white border rounded shadow-lg text-xs">
+                    {move || hits.get().is_empty().then(|| view! {
+                        <li class="px-2 py-1 text-gray-500 italic">"No matches"</li>
+                    })}
+                    {move || hits.get().into_iter().map(|hit| {
+                        let location = match hit.kind {
+                            HitKind::Note => "Note".to_string(),
+                            HitKind::Todo => hit.list_name.iter().chain(hit.path.iter()).cloned().collect::<Vec<_>>().join(" › "),
+                        };
+                        let snippet = hit.snippet.clone();
+                        view! {
+                            <li
+                                class="px-2 py-1 cursor-pointer hover:bg-yellow-100"
+                                // mousedown, not click: the input's blur would close the list first
+                                on:mousedown=move |ev| {
+                                    ev.prevent_default();
+                                    select(hit.clone());
+                                }
+                            >
+                                <div class="text-gray-800 truncate">{highlighted_snippet(&snippet)}</div>
+                                <div class="text-[10px] text-gray-400 truncate">{location}</div>
+                            </li>
+                        }
+                    }).collect_view()}
+                </ul>
+            })}
+        </div>
+    }
+}
+
+/// Mirrors the backend `query::ViewLayout`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ViewLayout {
+    #[default]
+    Tree,
+    Flat,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SavedView {
+    id: u32,
+    name: String,
+    query: String,
+    layout: ViewLayout,
+    position: i32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct QueryResult {
+    todos: Vec<TodoItem>,
+    matches: Vec<u32>,
+}
+
+impl QueryResult {
+    /// The items to render. A flat view drops the ancestors and lifts every
+    /// match to the top level, a tree view keeps them marked as context.
+    fn into_todos(self, layout: ViewLayout) -> Vec<TodoItem> {
+        let matches = self.matches;
+        match layout {
+            ViewLayout::Tree => self
+                .todos
+                .into_iter()
+                .map(|todo| TodoItem { is_context: !matches.contains(&todo.id), ..todo })
+                .collect(),
+            ViewLayout::Flat => self
+                .todos
+                .into_iter()
+                .filter(|todo| matches.contains(&todo.id))
+                .map(|todo| TodoItem { parent_id: None, ..todo })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RunQueryArgs {
+    query: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CreateViewArgs {
+    name: String,
+    query: String,
+    layout: ViewLayout,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UpdateViewArgs {
+    id: u32,
+    name: String,
+    query: String,
+    layout: ViewLayout,
+}
+
+const QUERY_HELP: &str = "tag:work  done  !done  has:count|due|scheduled|repeat|tags|children  \
+due<7d  scheduled:today  due>=2026-01-31  p>=high  list:Home  \"some text\"  -term negates";
+
+#[component]
+fn SavedViewsView<F1, F2>(active_view: ReadSignal<Option<SavedView>>, on_open: F1, show_error: F2) -> impl IntoView
+where
+    F1: Fn(Option<SavedView>) + Copy + Send + 'static,
+    F2: Fn(AppError) + Copy + Send + 'static,
+{
+    let (views, set_views) = signal(Vec::<SavedView>::new());
+    // The view being edited, None while creating a new one
+    let (editing_id, set_editing_id) = signal(None::<u32>);
+    let (name, set_name) = signal(String::new());
+    let (query, set_query) = signal(String::new());
+    let (layout, set_layout) = signal(ViewLayout::Tree);
+
+    let reload = move || async move {
+        match invoke_cmd::<Vec<SavedView>>("load_views", JsValue::NULL).await {
+            Ok(list) => set_views.set(list),
+            Err(e) => show_error(e),
+        }
+    };
+
+    Effect::new(move |_| spawn_local(reload()));
+
+    let clear_form = move || {
+        set_editing_id.set(None);
+        set_name.set(String::new());
+        set_query.set(String::new());
+        set_layout.set(ViewLayout::Tree);
+    };
+
+    let edit = move |saved_view: SavedView| {
+        set_editing_id.set(Some(saved_view.id));
+        set_name.set(saved_view.name);
+        set_query.set(saved_view.query);
+        set_layout.set(saved_view.layout);
+    };
+
+    let save = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        let (name, query, layout) = (name.get_untracked(), query.get_untracked(), layout.get_untracked());
+        let editing = editing_id.get_untracked();
+        spawn_local(async move {
+            let saved = match editing {
+                Some(id) => {
+                    let args = serde_wasm_bindgen::to_value(&UpdateViewArgs { id, name, query, layout }).unwrap();
+                    invoke_cmd::<()>("update_view", args).await.map(|_| id)
+                }
+                None => {
+                    let args = serde_wasm_bindgen::to_value(&CreateViewArgs { name, query, layout }).unwrap();
+                    invoke_cmd::<u32>("create_view", args).await
+                }
+            };
+            match saved {
+                Ok(id) => {
+                    clear_form();
+                    reload().await;
+                    // Keep an open view showing what was just saved
+                    if active_view.get_untracked().is_some_and(|v| v.id == id) {
+                        on_open(views.get_untracked().into_iter().find(|v| v.id == id));
+                    }
+                }
+                Err(e) => show_error(e),
+            }
+        });
+    };
+
+    let delete = move |id: u32| {
+        spawn_local(async move {
+            let args = serde_wasm_bindgen::to_value(&ListItemArgs { id }).unwrap();
+            if let Err(e) = invoke_cmd::<()>("delete_view", args).await {
+                show_error(e);
+            }
+            if active_view.get_untracked().is_some_and(|v| v.id == id) {
+                on_open(None);
+            }
+            reload().await;
+        });
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 text-sm">
+            <form class="flex flex-col gap-1 text-xs" on:submit=save>
+                <div class="flex gap-1">
+                    <input
+                        class="w-28 border rounded px-1 bg-white"
+                        placeholder="Name"
+                        prop:value=move || name.get()
+                        on:input=move |ev| set_name.set(event_target_value(&ev))
+                    />
+                    <input
+                        class="flex-1 border rounded px-1 bg-white font-mono"
+                        placeholder="tag:work !done due<7d"
+                        prop:value=move || query.get()
+                        on:input=move |ev| set_query.set(event_target_value(&ev))
+                    />
+                    <select
+                        class="border rounded px-1 bg-white"
+                        on:change=move |ev| set_layout.set(if event_target_value(&ev) == "flat" { ViewLayout::Flat } else { ViewLayout::Tree })
+                    >
+                        <option value="tree" prop:selected=move || layout.get() == ViewLayout::Tree>"Tree"</option>
+                        <option value="flat" prop:selected=move || layout.get() == ViewLayout::Flat>"Flat"</option>
+                    </select>
+                    <button type="submit" class="text-green-600 hover:text-green-800 font-bold">
+                        {move || if editing_id.get().is_some() { "Save" } else { "+" }}
+                    </button>
+                    {move || editing_id.get().is_some().then(|| view! {
+                        <button type="button" class="text-gray-500 hover:text-gray-700" on:click=move |_| clear_form()>"Cancel"</button>
+                    })}
+                </div>
+                <div class="text-[10px] text-gray-500 font-mono">{QUERY_HELP}</div>
+            </form>
+            {move || views.get().is_empty().then(|| view! {
+                <div class="text-xs text-gray-500 italic">"No saved views yet"</div>
+            })}
+            <ul class="flex flex-col gap-1">
+                <For
+                    each=move || views.get()
+                    key=|saved_view| (saved_view.id, saved_view.name.clone(), saved_view.query.clone(), saved_view.layout)
+                    children=move |saved_view| {
+                        let id = saved_view.id;
+                        let for_open = saved_view.clone();
+                        let for_edit = saved_view.clone();
+                        view! {
+                            <li class=move || format!(
+                                "flex items-center gap-2 p-2 rounded border {}",
+                                if active_view.get().is_some_and(|v| v.id == id) { "bg-indigo-50 border-indigo-300" } else { "bg-white/70" }
+                            )>
+                                <button
+                                    class="flex-1 flex items-center gap-2 min-w-0 text-left"
+                                    on:click=move |_| on_open(Some(for_open.clone()))
+                                >
+                                    <span class="font-semibold text-gray-700">{saved_view.name}</span>
+                                    <code class="flex-1 truncate text-xs text-gray-500">{saved_view.query}</code>
+                                    <span class="text-[10px] text-gray-400">
+                                        {if saved_view.layout == ViewLayout::Flat { "flat" } else { "tree" }}
+                                    </span>
+                                </button>
+                                <button
+                                    class="text-xs text-gray-400 hover:text-gray-700"
+                                    title="Edit view"
+                                    on:click=move |_| edit(for_edit.clone())
+                                >
+                                    "✎"
+                                </button>
+                                <button
+                                    class="text-xs text-red-400 hover:text-red-600"
+                                    title="Delete view"
+                                    on:click=move |_| delete(id)
+                                >
+                                    "×"
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}